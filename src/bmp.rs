@@ -0,0 +1,142 @@
+//! Uncompressed Windows bitmaps, see
+//! <https://learn.microsoft.com/en-us/windows/win32/gdi/bitmap-storage>
+use crate::prelude::*;
+use std::io::{self, Write};
+
+const FILE_HEADER_LENGTH: u32 = 14;
+/// BITMAPINFOHEADER, enough for plain 24 bit images
+const INFO_HEADER_LENGTH: u32 = 40;
+/// BITMAPV4HEADER, which adds the channel masks needed to mark the fourth byte as alpha
+const V4_HEADER_LENGTH: u32 = 108;
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+/// 72 DPI, in pixels per meter
+const PIXELS_PER_METER: i32 = 2835;
+
+impl Canvas {
+    /// Streams the canvas out as a bottom up BMP: 24 bit BGR, or 32 bit BGRA if the canvas
+    /// has an alpha channel. Colors are clamped to 0..1 the same way as for PPM output.
+    pub fn write_bmp<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let (bytes_per_pixel, header_length) = if self.has_alpha() {
+            (4, V4_HEADER_LENGTH)
+        } else {
+            (3, INFO_HEADER_LENGTH)
+        };
+        // Every row is padded out to a multiple of four bytes
+        let row_length = (self.width as usize * bytes_per_pixel).next_multiple_of(4);
+        let image_size = u32::try_from(row_length * self.height as usize)
+            .ok()
+            .filter(|size| *size <= u32::MAX - FILE_HEADER_LENGTH - header_length)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "Image too large for BMP")
+            })?;
+        let data_offset = FILE_HEADER_LENGTH + header_length;
+
+        writer.write_all(b"BM")?;
+        writer.write_all(&(data_offset + image_size).to_le_bytes())?;
+        writer.write_all(&[0; 4])?;
+        writer.write_all(&data_offset.to_le_bytes())?;
+
+        let mut header = Vec::with_capacity(header_length as usize);
+        header.extend(header_length.to_le_bytes());
+        // A positive height means the rows are stored from the bottom of the image up
+        header.extend(self.width.to_le_bytes());
+        header.extend(self.height.to_le_bytes());
+        header.extend(1_u16.to_le_bytes());
+        header.extend((bytes_per_pixel as u16 * 8).to_le_bytes());
+        let compression = if self.has_alpha() {
+            BI_BITFIELDS
+        } else {
+            BI_RGB
+        };
+        header.extend(compression.to_le_bytes());
+        header.extend(image_size.to_le_bytes());
+        header.extend(PIXELS_PER_METER.to_le_bytes());
+        header.extend(PIXELS_PER_METER.to_le_bytes());
+        // No palette
+        header.extend([0; 8]);
+        if self.has_alpha() {
+            for mask in [0x00ff_0000_u32, 0x0000_ff00, 0x0000_00ff, 0xff00_0000] {
+                header.extend(mask.to_le_bytes());
+            }
+            header.extend(b"BGRs");
+            // Endpoints and gamma are only used by calibrated color spaces
+            header.resize(header_length as usize, 0);
+        }
+        writer.write_all(&header)?;
+
+        let rows: Vec<Vec<u8>> = self.rows_8_bit().collect();
+        for row in rows.iter().rev() {
+            let mut bytes = Vec::with_capacity(row_length);
+            for pixel in row.chunks_exact(bytes_per_pixel) {
+                bytes.extend([pixel[2], pixel[1], pixel[0]]);
+                bytes.extend(pixel.get(3));
+            }
+            bytes.resize(row_length, 0);
+            writer.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bmp(&self) -> Vec<u8> {
+        let mut bmp = Vec::new();
+        self.write_bmp(&mut bmp)
+            .expect("Writing to a Vec can't fail");
+        bmp
+    }
+}
+
+#[cfg(test)]
+mod bmp_tests {
+    use crate::prelude::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    }
+
+    #[test]
+    fn test_24_bit_bmp() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.write_pixel(1, 1, Color::new(0.0, 0.5, 1.0));
+
+        let bmp = canvas.to_bmp();
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(u32_at(&bmp, 2), 70);
+        assert_eq!(u32_at(&bmp, 10), 54);
+        assert_eq!(u32_at(&bmp, 14), 40);
+        assert_eq!(u32_at(&bmp, 18), 2);
+        assert_eq!(u32_at(&bmp, 22), 2);
+        assert_eq!(bmp[28], 24);
+        assert_eq!(u32_at(&bmp, 30), 0);
+        assert_eq!(u32_at(&bmp, 34), 16);
+
+        // The bottom row comes first, each row is BGR padded to 8 bytes
+        assert_eq!(bmp[54..62], [0, 0, 0, 255, 128, 0, 0, 0]);
+        assert_eq!(bmp[62..70], [0, 0, 255, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_32_bit_bmp_with_alpha() {
+        let mut canvas = Canvas::with_alpha(1, 2);
+        canvas.write_pixel_alpha(0, 0, Color::new(1.0, 0.5, 0.0), 0.2);
+
+        let bmp = canvas.to_bmp();
+        assert_eq!(u32_at(&bmp, 2), 14 + 108 + 8);
+        assert_eq!(u32_at(&bmp, 10), 122);
+        assert_eq!(u32_at(&bmp, 14), 108);
+        assert_eq!(bmp[28], 32);
+        assert_eq!(u32_at(&bmp, 30), 3);
+        assert_eq!(u32_at(&bmp, 54), 0x00ff_0000);
+        assert_eq!(u32_at(&bmp, 66), 0xff00_0000);
+        assert_eq!(&bmp[70..74], b"BGRs");
+
+        // BGRA with no padding, the untouched bottom pixel is transparent
+        assert_eq!(bmp[122..], [0, 0, 0, 0, 0, 128, 255, 51]);
+    }
+}
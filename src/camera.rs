@@ -0,0 +1,222 @@
+use crate::prelude::*;
+
+/// # Returns
+/// The transform that orients the world for an eye at `from`, looking towards `to`, with `up`
+/// roughly pointing upwards. It's applied to the world rather than the eye, so it moves
+/// the scene in front of a camera that always looks down -z.
+pub fn view_transform(from: &Point, to: &Point, up: &Vector) -> Matrix4 {
+    let mut forward = *to - *from;
+    forward.normalize();
+    let mut up = *up;
+    up.normalize();
+    let left = cross_product(&forward, &up);
+    // up is only approximate, so find the exact up from the other two axes
+    let true_up = cross_product(&left, &forward);
+
+    let orientation = Matrix4::from_rows([
+        [left.x, left.y, left.z, 0.0],
+        [true_up.x, true_up.y, true_up.z, 0.0],
+        [-forward.x, -forward.y, -forward.z, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    orientation * Matrix4::translation(-from.position.x, -from.position.y, -from.position.z)
+}
+
+/// Maps the canvas onto a plane one unit in front of the eye and casts a ray through each
+/// pixel of it
+pub struct Camera {
+    hsize: i32,
+    vsize: i32,
+    field_of_view: f32,
+    pub transform: Transform,
+    half_width: f32,
+    half_height: f32,
+    pixel_size: f32,
+}
+
+impl Camera {
+    /// Creates a camera rendering `hsize` by `vsize` pixels, with `field_of_view` being the
+    /// angle in radians the wider side covers
+    pub fn new(hsize: i32, vsize: i32, field_of_view: f32) -> Self {
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f32 / vsize as f32;
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+
+        Self {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Transform::default(),
+            half_width,
+            half_height,
+            pixel_size: half_width * 2.0 / hsize as f32,
+        }
+    }
+    pub fn hsize(&self) -> i32 {
+        self.hsize
+    }
+    pub fn vsize(&self) -> i32 {
+        self.vsize
+    }
+    pub fn field_of_view(&self) -> f32 {
+        self.field_of_view
+    }
+    /// # Returns
+    /// The width (and height) of a pixel on the plane one unit in front of the eye
+    pub fn pixel_size(&self) -> f32 {
+        self.pixel_size
+    }
+
+    /// # Returns
+    /// The ray from the eye through the centre of the pixel at x, y
+    pub fn ray_for_pixel(&self, x: i32, y: i32) -> Ray {
+        let x_offset = (x as f32 + 0.5) * self.pixel_size;
+        let y_offset = (y as f32 + 0.5) * self.pixel_size;
+        // The camera looks down -z, so +x is to the left
+        let world_x = self.half_width - x_offset;
+        let world_y = self.half_height - y_offset;
+
+        let inverse = self.transform.inverse();
+        let pixel = *inverse * Point::new(world_x, world_y, -1.0);
+        let origin = *inverse * Point::zero();
+        let mut direction = pixel - origin;
+        direction.normalize();
+        Ray::new(origin, direction)
+    }
+
+    pub fn render(&self, world: &World) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let ray = self.ray_for_pixel(x, y);
+                image.write_pixel(x, y, world.color_at(&ray));
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod camera_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+    use crate::world::world_tests::default_world;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_default_view_transform() {
+        let from = Point::zero();
+        let to = Point::new(0.0, 0.0, -1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        let transform = view_transform(&from, &to, &up);
+        assert!(transform.equals(&Matrix4::from_identity()));
+    }
+
+    #[test]
+    fn test_view_transform_looking_positive_z() {
+        let from = Point::zero();
+        let to = Point::new(0.0, 0.0, 1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        let transform = view_transform(&from, &to, &up);
+        assert!(transform.equals(&Matrix4::scaling(-1.0, 1.0, -1.0)));
+    }
+
+    #[test]
+    fn test_view_transform_moves_world() {
+        let from = Point::new(0.0, 0.0, 8.0);
+        let to = Point::zero();
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        let transform = view_transform(&from, &to, &up);
+        assert!(transform.equals(&Matrix4::translation(0.0, 0.0, -8.0)));
+    }
+
+    #[test]
+    fn test_arbitrary_view_transform() {
+        let from = Point::new(1.0, 3.0, 2.0);
+        let to = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(1.0, 1.0, 0.0);
+
+        let transform = view_transform(&from, &to, &up);
+        let expected = Matrix4::from_rows([
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        assert!(transform.equals(&expected));
+    }
+
+    #[test]
+    fn test_creating_camera() {
+        let camera = Camera::new(160, 120, PI / 2.0);
+
+        assert_eq!(camera.hsize(), 160);
+        assert_eq!(camera.vsize(), 120);
+        assert_eq!(camera.field_of_view(), PI / 2.0);
+        assert!(camera.transform.matrix().equals(&Matrix4::from_identity()));
+    }
+
+    #[test]
+    fn test_pixel_size() {
+        let horizontal = Camera::new(200, 125, PI / 2.0);
+        assert!((horizontal.pixel_size() - 0.01).abs() < 1e-5);
+        let vertical = Camera::new(125, 200, PI / 2.0);
+        assert!((vertical.pixel_size() - 0.01).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_ray_through_centre_of_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+        let ray = camera.ray_for_pixel(100, 50);
+
+        assert!(ray.origin.compare(&Point::zero()));
+        assert!(ray.direction.is_same(&Vector::new(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn test_ray_through_corner_of_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+        let ray = camera.ray_for_pixel(0, 0);
+
+        assert!(ray.origin.compare(&Point::zero()));
+        assert!(ray
+            .direction
+            .is_same(&Vector::new(0.66519, 0.33259, -0.66851)));
+    }
+
+    #[test]
+    fn test_ray_with_transformed_camera() {
+        let mut camera = Camera::new(201, 101, PI / 2.0);
+        let matrix = Matrix4::translation(0.0, -2.0, 5.0).rotate_y(PI / 4.0);
+        camera.transform = Transform::new(matrix).unwrap();
+        let ray = camera.ray_for_pixel(100, 50);
+
+        let half_root_two = f32::sqrt(2.0) / 2.0;
+        assert!(ray.origin.compare(&Point::new(0.0, 2.0, -5.0)));
+        assert!(ray
+            .direction
+            .is_same(&Vector::new(half_root_two, 0.0, -half_root_two)));
+    }
+
+    #[test]
+    fn test_rendering_world() {
+        let world = default_world();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::zero();
+        let up = Vector::new(0.0, 1.0, 0.0);
+        camera.transform = Transform::new(view_transform(&from, &to, &up)).unwrap();
+
+        let image = camera.render(&world);
+        assert!(image
+            .pixel_at(5, 5)
+            .is_same(&Color::new(0.38066, 0.47583, 0.2855)));
+    }
+}
@@ -1,8 +1,10 @@
 use crate::prelude::*;
 pub struct Canvas {
-    width: i32,
+    pub width: i32,
     pub height: i32,
-    pixels: Vec<Vec<Color>>,
+    pub(crate) pixels: Vec<Vec<Color>>,
+    /// Opacity from 0 (transparent) to 1 (opaque) for each pixel, if the canvas has any
+    pub(crate) alpha: Option<Vec<Vec<f32>>>,
 }
 impl Canvas {
     #[allow(clippy::cast_sign_loss)]
@@ -11,10 +13,29 @@ impl Canvas {
             width,
             height,
             pixels: vec![vec![Color::zero(); width as usize]; height as usize],
+            alpha: None,
         }
     }
+    /// Creates a canvas with an alpha channel where every pixel starts out transparent,
+    /// so only the parts that get drawn on cover whatever it is composited over
     #[allow(clippy::cast_sign_loss)]
+    pub fn with_alpha(width: i32, height: i32) -> Self {
+        Self {
+            alpha: Some(vec![vec![0.0; width as usize]; height as usize]),
+            ..Self::new(width, height)
+        }
+    }
+    pub fn has_alpha(&self) -> bool {
+        self.alpha.is_some()
+    }
+    /// Sets the color at x, y, making the pixel opaque if the canvas has an alpha channel
     pub fn write_pixel(&mut self, x: i32, y: i32, color: Color) {
+        self.write_pixel_alpha(x, y, color, 1.0);
+    }
+    /// Sets the color and opacity at x, y. The alpha is ignored if the canvas doesn't have
+    /// an alpha channel.
+    #[allow(clippy::cast_sign_loss)]
+    pub fn write_pixel_alpha(&mut self, x: i32, y: i32, color: Color, alpha: f32) {
         if x < 0 || x > self.width - 1 {
             return;
         }
@@ -23,30 +44,53 @@ impl Canvas {
         }
 
         self.pixels[y as usize][x as usize] = color;
+        if let Some(plane) = &mut self.alpha {
+            plane[y as usize][x as usize] = alpha;
+        }
+    }
+    /// # Returns
+    /// The color at x, y or black if the position is off the canvas
+    #[allow(clippy::cast_sign_loss)]
+    pub fn pixel_at(&self, x: i32, y: i32) -> Color {
+        if x < 0 || x > self.width - 1 || y < 0 || y > self.height - 1 {
+            return Color::zero();
+        }
+        self.pixels[y as usize][x as usize]
+    }
+    /// # Returns
+    /// The opacity at x, y, which is always 1 on a canvas without an alpha channel and 0 off
+    /// the canvas
+    #[allow(clippy::cast_sign_loss)]
+    pub fn alpha_at(&self, x: i32, y: i32) -> f32 {
+        if x < 0 || x > self.width - 1 || y < 0 || y > self.height - 1 {
+            return 0.0;
+        }
+        match &self.alpha {
+            Some(plane) => plane[y as usize][x as usize],
+            None => 1.0,
+        }
     }
 
-    pub fn to_pmm(&self) -> String {
-        const MAX_CHARS_PER_LINE: i32 = 58;
-
-        let mut header = format!("P3\n{} {}\n255\n", self.width, self.height);
-        let mut pixel_data = String::new();
-        let mut character_count = 0;
-
-        for row in &self.pixels {
-            for pixel in row {
-                let pixel_str = format!("{} {} {} ", pixel.red, pixel.green, pixel.blue);
-                pixel_data += &*pixel_str;
-                character_count += pixel_str.len() as i32;
-            }
-            if character_count > MAX_CHARS_PER_LINE {
-                character_count = 0;
-                pixel_data += "\n";
+    /// # Returns
+    /// Each row as 8 bit RGB samples, followed by alpha if the canvas has an alpha channel
+    pub(crate) fn rows_8_bit(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        self.pixels.iter().enumerate().map(|(y, row)| {
+            let mut bytes = Vec::with_capacity(row.len() * 4);
+            for (x, pixel) in row.iter().enumerate() {
+                bytes.extend(pixel.to_rgb8());
+                if let Some(plane) = &self.alpha {
+                    bytes.push(scale_channel(plane[y][x], 255) as u8);
+                }
             }
+            bytes
+        })
+    }
 
-            pixel_data += "\n";
-        }
-        header += pixel_data.as_str();
-        header
+    pub fn to_pmm(&self) -> String {
+        let mut ppm = Vec::new();
+        self.write_ppm(&mut ppm, PpmFormat::Plain, 255)
+            .expect("Writing to a Vec can't fail");
+        String::from_utf8(ppm).expect("Plain PPMs are ASCII")
     }
 }
 
@@ -78,11 +122,39 @@ mod canvas_tests {
         assert_eq!(canvas.pixels[1][1].red, 20.0)
     }
 
+    #[test]
+    fn test_pixel_at() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.write_pixel(2, 3, Color::new(1.0, 0.5, 0.25));
+
+        assert_eq!(canvas.pixel_at(2, 3), Color::new(1.0, 0.5, 0.25));
+        assert_eq!(canvas.pixel_at(3, 2), Color::zero());
+        assert_eq!(canvas.pixel_at(-1, 20), Color::zero());
+    }
+
     #[test]
     fn test_canvas_to_pmm() {
         let mut canvas = Canvas::new(2, 2);
         canvas.write_pixel(0, 0, Color::new(255.0, 0.0, 0.0));
-        let expected_string = String::from("P3\n2 2\n255\n255 0 0 0 0 0 \n0 0 0 0 0 0 \n");
+        let expected_string = String::from("P3\n2 2\n255\n255 0 0 0 0 0\n0 0 0 0 0 0\n");
         assert_eq!(canvas.to_pmm(), expected_string);
     }
+
+    #[test]
+    fn test_alpha_channel() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_pixel_alpha(0, 0, Color::new(1.0, 0.0, 0.0), 0.5);
+        assert!(!canvas.has_alpha());
+        assert_eq!(canvas.alpha_at(0, 0), 1.0);
+
+        let mut canvas = Canvas::with_alpha(2, 2);
+        canvas.write_pixel_alpha(0, 0, Color::new(1.0, 0.0, 0.0), 0.5);
+        canvas.write_pixel(1, 0, Color::new(0.0, 1.0, 0.0));
+        assert!(canvas.has_alpha());
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(canvas.alpha_at(0, 0), 0.5);
+        assert_eq!(canvas.alpha_at(1, 0), 1.0);
+        assert_eq!(canvas.alpha_at(0, 1), 0.0);
+        assert_eq!(canvas.alpha_at(5, 5), 0.0);
+    }
 }
@@ -1,4 +1,8 @@
-#[derive(Copy, Clone)]
+use crate::compare_float;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Color {
     pub(crate) red: f32,
     pub(crate) green: f32,
@@ -6,50 +10,275 @@ pub struct Color {
 }
 
 impl Color {
-    pub fn new(red: f32, green: f32, blue: f32) -> Self {
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+    pub const RED: Color = Color::new(1.0, 0.0, 0.0);
+    pub const GREEN: Color = Color::new(0.0, 1.0, 0.0);
+    pub const BLUE: Color = Color::new(0.0, 0.0, 1.0);
+    pub const YELLOW: Color = Color::new(1.0, 1.0, 0.0);
+    pub const CYAN: Color = Color::new(0.0, 1.0, 1.0);
+    pub const MAGENTA: Color = Color::new(1.0, 0.0, 1.0);
+
+    pub const fn new(red: f32, green: f32, blue: f32) -> Self {
         Self { red, green, blue }
     }
     pub fn from_red() -> Self {
-        Self::new(255.0, 0.0, 0.0)
+        Self::RED
     }
     pub fn from_green() -> Self {
-        Self::new(0.0, 255.0, 0.0)
+        Self::GREEN
     }
     pub fn from_blue() -> Self {
-        Self::new(0.0, 0.0, 255.0)
+        Self::BLUE
     }
     pub fn zero() -> Self {
-        Self::new(0.0, 0.0, 0.0)
+        Self::BLACK
+    }
+    /// Parses a CSS style hex color like `#ff8800`, `ff8800` or the short form `#f80`.
+    /// The channels are only scaled to 0..1, not converted from sRGB to linear.
+    pub fn from_hex(hex: &str) -> Result<Color, ColorError> {
+        let invalid = || ColorError::InvalidHex(hex.to_string());
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| invalid());
+        let [red, green, blue] = match digits.len() {
+            // Each digit of the short form is doubled, so f becomes ff
+            3 => [0, 1, 2].map(|index| channel(&digits[index..=index]).map(|value| value * 17)),
+            6 => [0, 2, 4].map(|index| channel(&digits[index..index + 2])),
+            _ => return Err(invalid()),
+        };
+        Ok(Color::from_rgb8([red?, green?, blue?]))
+    }
+    pub fn from_rgb8([red, green, blue]: [u8; 3]) -> Self {
+        Self::new(
+            f32::from(red) / 255.0,
+            f32::from(green) / 255.0,
+            f32::from(blue) / 255.0,
+        )
+    }
+    /// Creates a color from a hue in degrees and saturation and value in 0..1
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+        Self::from_hue(hue, chroma, value - chroma)
+    }
+    /// Creates a color from a hue in degrees and saturation and lightness in 0..1
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+    /// Walks around the hue hexagon, where `chroma` is the spread between the largest and
+    /// smallest channel and `lightest` is added to all three
+    fn from_hue(hue: f32, chroma: f32, lightest: f32) -> Self {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let middle = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (red, green, blue) = match sector as u32 {
+            0 => (chroma, middle, 0.0),
+            1 => (middle, chroma, 0.0),
+            2 => (0.0, chroma, middle),
+            3 => (0.0, middle, chroma),
+            4 => (middle, 0.0, chroma),
+            _ => (chroma, 0.0, middle),
+        };
+        Self::new(red + lightest, green + lightest, blue + lightest)
+    }
+    pub fn is_same(&self, color: &Color) -> bool {
+        compare_float(self.red, color.red)
+            && compare_float(self.green, color.green)
+            && compare_float(self.blue, color.blue)
+    }
+    /// Clamps each channel to 0..1 and scales it up to an integer in 0..=max_value,
+    /// e.g. 255 for 8 bit images
+    pub fn to_scaled(self, max_value: u16) -> [u16; 3] {
+        [self.red, self.green, self.blue].map(|channel| scale_channel(channel, max_value))
+    }
+
+    /// Clamps each channel to 0..1 and rounds it to a byte
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_rgb8(self) -> [u8; 3] {
+        self.to_scaled(255).map(|channel| channel as u8)
+    }
+    /// # Returns
+    /// The hue in degrees, and the saturation and value
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (hue, largest, smallest) = self.hue();
+        let saturation = if largest == 0.0 {
+            0.0
+        } else {
+            (largest - smallest) / largest
+        };
+        (hue, saturation, largest)
+    }
+    /// # Returns
+    /// The hue in degrees, and the saturation and lightness
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (hue, largest, smallest) = self.hue();
+        let lightness = (largest + smallest) / 2.0;
+        let saturation = if lightness == 0.0 || lightness == 1.0 {
+            0.0
+        } else {
+            (largest - smallest) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+    /// # Returns
+    /// The hue in degrees, which is 0 for grays, along with the largest and smallest channel
+    fn hue(self) -> (f32, f32, f32) {
+        let largest = self.red.max(self.green).max(self.blue);
+        let smallest = self.red.min(self.green).min(self.blue);
+        let chroma = largest - smallest;
+
+        let sector = if chroma == 0.0 {
+            0.0
+        } else if largest == self.red {
+            ((self.green - self.blue) / chroma).rem_euclid(6.0)
+        } else if largest == self.green {
+            (self.blue - self.red) / chroma + 2.0
+        } else {
+            (self.red - self.green) / chroma + 4.0
+        };
+        (sector * 60.0, largest, smallest)
+    }
+    /// Clamps each channel to 0..1
+    pub fn clamp(self) -> Self {
+        Self::new(
+            self.red.clamp(0.0, 1.0),
+            self.green.clamp(0.0, 1.0),
+            self.blue.clamp(0.0, 1.0),
+        )
+    }
+    /// How bright the color looks to the eye, using the Rec. 709 weights for each channel
+    pub fn luminance(self) -> f32 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+    /// Blends linearly from this color at t = 0 to `other` at t = 1
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    pub fn haramard_product(&mut self, color: &Color) {
+        *self *= *color;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorError {
+    /// A string that isn't 3 or 6 hex digits, optionally starting with `#`
+    InvalidHex(String),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorError::InvalidHex(hex) => write!(f, "{:?} is not a valid hex color", hex),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+pub(crate) fn scale_channel(channel: f32, max_value: u16) -> u16 {
+    (channel.clamp(0.0, 1.0) * f32::from(max_value)).round() as u16
+}
+
+impl Add for Color {
+    type Output = Color;
+
+    fn add(self, rhs: Color) -> Self::Output {
+        Color::new(
+            self.red + rhs.red,
+            self.green + rhs.green,
+            self.blue + rhs.blue,
+        )
+    }
+}
+
+impl Sub for Color {
+    type Output = Color;
+
+    fn sub(self, rhs: Color) -> Self::Output {
+        Color::new(
+            self.red - rhs.red,
+            self.green - rhs.green,
+            self.blue - rhs.blue,
+        )
+    }
+}
+
+/// Multiplying two colors blends them channel by channel (the Hadamard product)
+impl Mul for Color {
+    type Output = Color;
+
+    fn mul(self, rhs: Color) -> Self::Output {
+        Color::new(
+            self.red * rhs.red,
+            self.green * rhs.green,
+            self.blue * rhs.blue,
+        )
+    }
+}
+
+impl Mul<f32> for Color {
+    type Output = Color;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Color::new(self.red * rhs, self.green * rhs, self.blue * rhs)
+    }
+}
+
+impl Mul<Color> for f32 {
+    type Output = Color;
+
+    fn mul(self, rhs: Color) -> Self::Output {
+        rhs * self
     }
-    fn copy(color: &Color) -> Self {
-        Self::new(color.red, color.green, color.blue)
+}
+
+impl Div<f32> for Color {
+    type Output = Color;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Color::new(self.red / rhs, self.green / rhs, self.blue / rhs)
     }
-    fn clone(&self) -> Self {
-        Color::copy(self)
+}
+
+impl AddAssign for Color {
+    fn add_assign(&mut self, rhs: Color) {
+        *self = *self + rhs;
     }
+}
 
-    fn subtract(&mut self, color: &Color) {
-        self.green -= color.green;
-        self.red -= color.red;
-        self.blue -= color.blue;
+impl SubAssign for Color {
+    fn sub_assign(&mut self, rhs: Color) {
+        *self = *self - rhs;
     }
+}
 
-    fn add(&mut self, color: &Color) {
-        self.green += color.green;
-        self.red += color.red;
-        self.blue += color.blue;
+impl MulAssign for Color {
+    fn mul_assign(&mut self, rhs: Color) {
+        *self = *self * rhs;
     }
+}
 
-    fn haramard_product(&mut self, color: &Color) {
-        self.green *= color.green;
-        self.red *= color.red;
-        self.blue *= color.blue;
+impl MulAssign<f32> for Color {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign<f32> for Color {
+    fn div_assign(&mut self, rhs: f32) {
+        *self = *self / rhs;
     }
 }
 
 #[cfg(test)]
 mod color_tests {
-    use crate::color::Color;
+    use crate::color::{Color, ColorError};
+    use crate::compare_float;
 
     #[test]
     fn test_colors() {
@@ -69,4 +298,127 @@ mod color_tests {
         assert_eq!(a.green, 5.0);
         assert_eq!(a.blue, 5.0);
     }
+
+    #[test]
+    fn test_color_operators() {
+        let a = Color::new(0.9, 0.6, 0.75);
+        let b = Color::new(0.7, 0.1, 0.25);
+
+        assert!((a + b).is_same(&Color::new(1.6, 0.7, 1.0)));
+        assert!((a - b).is_same(&Color::new(0.2, 0.5, 0.5)));
+        assert!((Color::new(0.2, 0.3, 0.4) * 2.0).is_same(&Color::new(0.4, 0.6, 0.8)));
+        assert!((Color::new(1.0, 0.2, 0.4) * Color::new(0.9, 1.0, 0.1))
+            .is_same(&Color::new(0.9, 0.2, 0.04)));
+
+        let mut c = a;
+        c += b;
+        c -= b;
+        assert!(c.is_same(&a));
+        c *= 2.0;
+        assert!(c.is_same(&(2.0 * a)));
+        assert!((c / 2.0).is_same(&a));
+    }
+
+    #[test]
+    fn test_to_scaled() {
+        assert_eq!(Color::new(1.0, 0.5, 0.0).to_scaled(255), [255, 128, 0]);
+        assert_eq!(Color::new(1.5, -0.5, 0.25).to_scaled(255), [255, 0, 64]);
+        assert_eq!(
+            Color::new(1.0, 0.5, 0.0).to_scaled(65535),
+            [65535, 32768, 0]
+        );
+    }
+
+    #[test]
+    fn test_named_colors() {
+        assert_eq!(Color::from_red(), Color::RED);
+        assert_eq!(Color::from_green(), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(Color::from_blue(), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(Color::zero(), Color::BLACK);
+        assert_eq!(Color::RED + Color::GREEN + Color::BLUE, Color::WHITE);
+        assert_eq!(Color::RED + Color::GREEN, Color::YELLOW);
+    }
+
+    #[test]
+    fn test_clamp_luminance_and_lerp() {
+        let color = Color::new(1.5, -0.5, 0.25);
+        assert_eq!(color.clamp(), Color::new(1.0, 0.0, 0.25));
+        assert_eq!(color.to_rgb8(), [255, 0, 64]);
+
+        assert!(compare_float(Color::WHITE.luminance(), 1.0));
+        assert!(compare_float(Color::GREEN.luminance(), 0.7152));
+        assert!(Color::GREEN.luminance() > Color::RED.luminance());
+
+        let a = Color::new(0.0, 0.5, 1.0);
+        let b = Color::new(1.0, 0.5, 0.0);
+        assert!(a.lerp(b, 0.0).is_same(&a));
+        assert!(a.lerp(b, 1.0).is_same(&b));
+        assert!(a.lerp(b, 0.25).is_same(&Color::new(0.25, 0.5, 0.75)));
+
+        let mut c = Color::new(1.0, 2.0, 3.0);
+        c /= 2.0;
+        assert!(c.is_same(&Color::new(0.5, 1.0, 1.5)));
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(
+            Color::from_hex("#ff8800"),
+            Ok(Color::new(1.0, 136.0 / 255.0, 0.0))
+        );
+        assert_eq!(Color::from_hex("FF8800"), Color::from_hex("#ff8800"));
+        assert_eq!(Color::from_hex("#f80"), Color::from_hex("#ff8800"));
+        assert_eq!(
+            Color::from_rgb8([255, 136, 0]),
+            Color::from_hex("#ff8800").unwrap()
+        );
+
+        for invalid in ["#ff880", "#gg8800", "", "#", "#+f8800", "#ff8800ff"] {
+            assert_eq!(
+                Color::from_hex(invalid),
+                Err(ColorError::InvalidHex(invalid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert!(Color::from_hsv(0.0, 1.0, 1.0).is_same(&Color::RED));
+        assert!(Color::from_hsv(120.0, 1.0, 1.0).is_same(&Color::GREEN));
+        assert!(Color::from_hsv(240.0, 1.0, 0.5).is_same(&Color::new(0.0, 0.0, 0.5)));
+        assert!(Color::from_hsv(30.0, 0.5, 1.0).is_same(&Color::new(1.0, 0.75, 0.5)));
+        assert!(Color::from_hsv(-60.0, 1.0, 1.0).is_same(&Color::MAGENTA));
+        assert!(Color::from_hsv(200.0, 0.0, 0.3).is_same(&Color::new(0.3, 0.3, 0.3)));
+
+        let (hue, saturation, value) = Color::new(1.0, 0.75, 0.5).to_hsv();
+        assert!(compare_float(hue, 30.0));
+        assert!(compare_float(saturation, 0.5));
+        assert!(compare_float(value, 1.0));
+        assert_eq!(Color::BLACK.to_hsv(), (0.0, 0.0, 0.0));
+        assert!(compare_float(Color::MAGENTA.to_hsv().0, 300.0));
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert!(Color::from_hsl(0.0, 1.0, 0.5).is_same(&Color::RED));
+        assert!(Color::from_hsl(180.0, 1.0, 0.25).is_same(&Color::new(0.0, 0.5, 0.5)));
+        assert!(Color::from_hsl(0.0, 0.0, 1.0).is_same(&Color::WHITE));
+
+        let (hue, saturation, lightness) = Color::new(0.0, 0.5, 0.5).to_hsl();
+        assert!(compare_float(hue, 180.0));
+        assert!(compare_float(saturation, 1.0));
+        assert!(compare_float(lightness, 0.25));
+        assert_eq!(Color::WHITE.to_hsl(), (0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_hue_round_trips() {
+        for step in 0..36 {
+            let color = Color::from_hsv(step as f32 * 10.0, 0.8, 0.6);
+            let (hue, saturation, value) = color.to_hsv();
+            assert!(Color::from_hsv(hue, saturation, value).is_same(&color));
+            let (hue, saturation, lightness) = color.to_hsl();
+            assert!(Color::from_hsl(hue, saturation, lightness).is_same(&color));
+        }
+    }
 }
@@ -0,0 +1,226 @@
+use crate::cylinder::intersect_caps;
+use crate::prelude::*;
+
+/// A double napped cone around the y axis, two cones meeting tip to tip at the origin with a
+/// radius equal to the distance from it along y
+pub struct Cone {
+    pub transform: Transform,
+    pub material: Material,
+    /// Where the cone is cut off along y, exclusive. Infinite by default.
+    pub minimum: f32,
+    pub maximum: f32,
+    /// Whether the ends of a truncated cone are capped, or left open like a lampshade
+    pub closed: bool,
+}
+
+impl Cone {
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+            minimum: f32::NEG_INFINITY,
+            maximum: f32::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Cone {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    fn material(&self) -> &Material {
+        &self.material
+    }
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let (origin, direction) = (ray.origin.position, ray.direction);
+        let in_range = |t: &f32| {
+            let y = origin.y + t * direction.y;
+            self.minimum < y && y < self.maximum
+        };
+        let mut ts = Vec::new();
+
+        let a = direction.x * direction.x - direction.y * direction.y + direction.z * direction.z;
+        let b = 2.0 * (origin.x * direction.x - origin.y * direction.y + origin.z * direction.z);
+        let c = origin.x * origin.x - origin.y * origin.y + origin.z * origin.z;
+        // The transform scales the direction, and a and b with it, so they're compared
+        // relative to its squared length and length
+        let length_squared = direction.dot_product(&direction);
+        if a.abs() < EPSILON * EPSILON * length_squared {
+            // The ray is parallel to one of the cone's sides, so it can only cross the other
+            // half, once. If b is zero as well the ray misses or lies along the surface.
+            if b.abs() >= EPSILON * EPSILON * length_squared.sqrt() {
+                ts.extend(Some(-c / (2.0 * b)).filter(in_range));
+            }
+        } else {
+            // A ray grazing the cone should have a discriminant of zero, but the subtraction
+            // loses enough precision to leave it a few ulps of b² either side
+            let discriminant = match b * b - 4.0 * a * c {
+                grazing if grazing < 0.0 && grazing > -4.0 * f32::EPSILON * b * b => 0.0,
+                discriminant => discriminant,
+            };
+            if discriminant >= 0.0 {
+                let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+                let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+                ts.extend([t1, t2].into_iter().filter(in_range));
+            }
+        }
+
+        if self.closed {
+            ts.extend(intersect_caps(ray, self.minimum, self.maximum, f32::abs));
+        }
+        ts.into_iter().map(|t| Intersection::new(t, self)).collect()
+    }
+
+    /// Points inside the radius at either end of a closed cone are on a cap, everything else
+    /// is on the side, where the normal leans away from the tip
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let Vector { x, y, z } = point.position;
+        let distance = x * x + z * z;
+
+        if self.closed && distance < y * y && y >= self.maximum - EPSILON {
+            Vector::new(0.0, 1.0, 0.0)
+        } else if self.closed && distance < y * y && y <= self.minimum + EPSILON {
+            Vector::new(0.0, -1.0, 0.0)
+        } else {
+            let radius = distance.sqrt();
+            Vector::new(x, if y > 0.0 { -radius } else { radius }, z)
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        let radius = self.minimum.abs().max(self.maximum.abs());
+        Bounds::new(
+            Point::new(-radius, self.minimum, -radius),
+            Point::new(radius, self.maximum, radius),
+        )
+    }
+}
+
+#[cfg(test)]
+mod cone_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+    use crate::shape::shape_tests::ray;
+
+    #[test]
+    fn test_ray_hits_cone() {
+        let cone = Cone::new();
+        let cases = [
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 5.0, 5.0),
+            ((0.0, 0.0, -5.0), (1.0, 1.0, 1.0), 8.66025, 8.66025),
+            ((1.0, 1.0, -5.0), (-0.5, -1.0, 1.0), 4.55006, 49.44994),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let xs = cone.local_intersect(&ray(origin, direction));
+            assert_eq!(xs.len(), 2);
+            // The far hit of the last ray is large enough for f32 rounding to show
+            assert!((xs[0].t - t1).abs() < 0.05, "{} != {}", xs[0].t, t1);
+            assert!((xs[1].t - t2).abs() < 0.05, "{} != {}", xs[1].t, t2);
+        }
+    }
+
+    #[test]
+    fn test_ray_parallel_to_one_half() {
+        let cone = Cone::new();
+        let xs = cone.local_intersect(&ray((0.0, 0.0, -1.0), (0.0, 1.0, 1.0)));
+
+        assert_eq!(xs.len(), 1);
+        assert!(compare_float(xs[0].t, 0.35355));
+    }
+
+    #[test]
+    fn test_capped_cone() {
+        let mut cone = Cone::new();
+        cone.minimum = -0.5;
+        cone.maximum = 0.5;
+        cone.closed = true;
+        let cases = [
+            ((0.0, 0.0, -5.0), (0.0, 1.0, 0.0), 0),
+            ((0.0, 0.0, -0.25), (0.0, 1.0, 1.0), 2),
+            ((0.0, 0.0, -0.25), (0.0, 1.0, 0.0), 4),
+        ];
+
+        for (origin, direction, count) in cases {
+            let xs = cone.local_intersect(&ray(origin, direction));
+            assert_eq!(xs.len(), count, "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_normal_on_cone() {
+        let cone = Cone::new();
+        let cases = [
+            ((0.0, 0.0, 0.0), (0.0, 0.0, 0.0)),
+            ((1.0, 1.0, 1.0), (1.0, -f32::sqrt(2.0), 1.0)),
+            ((-1.0, -1.0, 0.0), (-1.0, 1.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            let found = cone.local_normal_at(Point::new(point.0, point.1, point.2));
+            assert!(found.is_same(&Vector::new(normal.0, normal.1, normal.2)));
+        }
+    }
+
+    #[test]
+    fn test_normal_on_cone_caps() {
+        let mut cone = Cone::new();
+        cone.minimum = -1.0;
+        cone.maximum = 2.0;
+        cone.closed = true;
+
+        let normal = cone.local_normal_at(Point::new(0.5, 2.0, 0.5));
+        assert!(normal.is_same(&Vector::new(0.0, 1.0, 0.0)));
+        let normal = cone.local_normal_at(Point::new(0.0, -1.0, 0.5));
+        assert!(normal.is_same(&Vector::new(0.0, -1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_cone_bounds() {
+        let mut cone = Cone::new();
+        cone.minimum = -3.0;
+        cone.maximum = 2.0;
+        let bounds = cone.bounds();
+
+        assert_eq!(bounds.min, Point::new(-3.0, -3.0, -3.0));
+        assert_eq!(bounds.max, Point::new(3.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_normal_near_open_end() {
+        let mut cone = Cone::new();
+        cone.minimum = 0.0;
+        cone.maximum = 1.0;
+
+        // Just inside the rim of an open cone is still the side
+        let normal = cone.local_normal_at(Point::new(0.0, 0.995, -0.994_99));
+        assert!(normal.is_same(&Vector::new(0.0, -0.995, -0.995)));
+    }
+
+    #[test]
+    fn test_scaled_cone() {
+        // Stretched along y the direction shrinks in object space, but it still isn't
+        // parallel to the sides
+        let mut cone = Cone::new();
+        cone.minimum = 0.0;
+        cone.maximum = 1.0;
+        cone.closed = true;
+        cone.transform = Transform::new(Matrix4::scaling(1.0, 200.0, 1.0)).unwrap();
+
+        let xs = cone.intersect(&ray((0.5, 300.0, 0.0), (0.0, -1.0, 0.0)));
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, 100.0));
+        assert!(compare_float(xs[1].t, 200.0));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::prelude::*;
+
+/// An axis aligned cube spanning -1 to 1 on every axis
+pub struct Cube {
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Cube {
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Cube {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    fn material(&self) -> &Material {
+        &self.material
+    }
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    /// The cube is its own bounding box, so it's hit wherever the box is
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match self.bounds().intersect(ray) {
+            Some((t_min, t_max)) => Intersections::new(vec![
+                Intersection::new(t_min, self),
+                Intersection::new(t_max, self),
+            ]),
+            None => Intersections::new(vec![]),
+        }
+    }
+
+    /// The face a point is on is the axis it's furthest along, so the normal points along it
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let Vector { x, y, z } = point.position;
+        let largest = x.abs().max(y.abs()).max(z.abs());
+
+        if largest == x.abs() {
+            Vector::new(x, 0.0, 0.0)
+        } else if largest == y.abs() {
+            Vector::new(0.0, y, 0.0)
+        } else {
+            Vector::new(0.0, 0.0, z)
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod cube_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_ray_intersects_cube() {
+        let cube = Cube::new();
+        let cases = [
+            ((5.0, 0.5, 0.0), (-1.0, 0.0, 0.0), 4.0, 6.0),
+            ((-5.0, 0.5, 0.0), (1.0, 0.0, 0.0), 4.0, 6.0),
+            ((0.5, 5.0, 0.0), (0.0, -1.0, 0.0), 4.0, 6.0),
+            ((0.5, -5.0, 0.0), (0.0, 1.0, 0.0), 4.0, 6.0),
+            ((0.5, 0.0, 5.0), (0.0, 0.0, -1.0), 4.0, 6.0),
+            ((0.5, 0.0, -5.0), (0.0, 0.0, 1.0), 4.0, 6.0),
+            // From inside
+            ((0.0, 0.5, 0.0), (0.0, 0.0, 1.0), -1.0, 1.0),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let ray = Ray::new(
+                Point::new(origin.0, origin.1, origin.2),
+                Vector::new(direction.0, direction.1, direction.2),
+            );
+            let xs = cube.local_intersect(&ray);
+
+            assert_eq!(xs.len(), 2);
+            assert!(compare_float(xs[0].t, t1), "{:?}", origin);
+            assert!(compare_float(xs[1].t, t2), "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_ray_misses_cube() {
+        let cube = Cube::new();
+        let cases = [
+            ((-2.0, 0.0, 0.0), (0.2673, 0.5345, 0.8018)),
+            ((0.0, -2.0, 0.0), (0.8018, 0.2673, 0.5345)),
+            ((0.0, 0.0, -2.0), (0.5345, 0.8018, 0.2673)),
+            ((2.0, 0.0, 2.0), (0.0, 0.0, -1.0)),
+            ((0.0, 2.0, 2.0), (0.0, -1.0, 0.0)),
+            ((2.0, 2.0, 0.0), (-1.0, 0.0, 0.0)),
+        ];
+
+        for (origin, direction) in cases {
+            let ray = Ray::new(
+                Point::new(origin.0, origin.1, origin.2),
+                Vector::new(direction.0, direction.1, direction.2),
+            );
+            assert!(cube.local_intersect(&ray).is_empty(), "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_normal_on_cube() {
+        let cube = Cube::new();
+        let cases = [
+            ((1.0, 0.5, -0.8), (1.0, 0.0, 0.0)),
+            ((-1.0, -0.2, 0.9), (-1.0, 0.0, 0.0)),
+            ((-0.4, 1.0, -0.1), (0.0, 1.0, 0.0)),
+            ((0.3, -1.0, -0.7), (0.0, -1.0, 0.0)),
+            ((-0.6, 0.3, 1.0), (0.0, 0.0, 1.0)),
+            ((0.4, 0.4, -1.0), (0.0, 0.0, -1.0)),
+            // Corners pick the x face
+            ((1.0, 1.0, 1.0), (1.0, 0.0, 0.0)),
+            ((-1.0, -1.0, -1.0), (-1.0, 0.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            let found = cube.local_normal_at(Point::new(point.0, point.1, point.2));
+            assert!(found.is_same(&Vector::new(normal.0, normal.1, normal.2)));
+        }
+    }
+
+    #[test]
+    fn test_transformed_cube() {
+        let mut cube = Cube::new();
+        cube.transform =
+            Transform::new(Matrix4::scaling(2.0, 1.0, 1.0).translate(0.0, 0.0, 5.0)).unwrap();
+        let ray = Ray::new(Point::new(1.5, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = cube.intersect(&ray);
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, 4.0));
+        assert!(compare_float(xs[1].t, 6.0));
+        assert!(cube
+            .normal_at(Point::new(1.5, 0.0, 4.0))
+            .is_same(&Vector::new(0.0, 0.0, -1.0)));
+    }
+}
@@ -0,0 +1,289 @@
+use crate::prelude::*;
+
+/// A cylinder of radius 1 around the y axis
+pub struct Cylinder {
+    pub transform: Transform,
+    pub material: Material,
+    /// Where the cylinder is cut off along y, exclusive. Infinite by default.
+    pub minimum: f32,
+    pub maximum: f32,
+    /// Whether the ends of a truncated cylinder are capped, or left open like a pipe
+    pub closed: bool,
+}
+
+impl Cylinder {
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+            minimum: f32::NEG_INFINITY,
+            maximum: f32::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Finds where a ray crosses the flat caps at `minimum` and `maximum` on the y axis, for any
+/// shape whose cross section is a circle around that axis
+/// # Returns
+/// The t values of the hits that land inside the circle of `radius_at(y)` at each cap
+pub(crate) fn intersect_caps(
+    ray: &Ray,
+    minimum: f32,
+    maximum: f32,
+    radius_at: impl Fn(f32) -> f32,
+) -> Vec<f32> {
+    // A ray parallel to the caps divides by zero, and the infinite point it gets is never
+    // inside the radius
+    [minimum, maximum]
+        .into_iter()
+        .filter(|y| y.is_finite())
+        .filter_map(|y| {
+            let t = (y - ray.origin.position.y) / ray.direction.y;
+            let point = ray.position(t).position;
+            let radius = radius_at(y);
+            // Rounding can leave a hit on the very edge just outside of it
+            (point.x * point.x + point.z * point.z <= radius * radius + EPSILON).then_some(t)
+        })
+        .collect()
+}
+
+impl Shape for Cylinder {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    fn material(&self) -> &Material {
+        &self.material
+    }
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let (origin, direction) = (ray.origin.position, ray.direction);
+        let mut ts = Vec::new();
+
+        // a is the squared length of the direction across the axis. The transform scales the
+        // direction, so it's compared relative to the whole squared length, making this the
+        // same tolerance on the angle a plane uses for parallel rays.
+        let a = direction.x * direction.x + direction.z * direction.z;
+        if a >= EPSILON * EPSILON * direction.dot_product(&direction) {
+            let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+            let c = origin.x * origin.x + origin.z * origin.z - 1.0;
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant >= 0.0 {
+                let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+                let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+                ts.extend([t1, t2].into_iter().filter(|t| {
+                    let y = origin.y + t * direction.y;
+                    self.minimum < y && y < self.maximum
+                }));
+            }
+        }
+
+        if self.closed {
+            ts.extend(intersect_caps(ray, self.minimum, self.maximum, |_| 1.0));
+        }
+        ts.into_iter().map(|t| Intersection::new(t, self)).collect()
+    }
+
+    /// Points inside the radius at either end of a closed cylinder are on a cap, everything else
+    /// is on the side
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let Vector { x, y, z } = point.position;
+        let distance = x * x + z * z;
+
+        if self.closed && distance < 1.0 && y >= self.maximum - EPSILON {
+            Vector::new(0.0, 1.0, 0.0)
+        } else if self.closed && distance < 1.0 && y <= self.minimum + EPSILON {
+            Vector::new(0.0, -1.0, 0.0)
+        } else {
+            Vector::new(x, 0.0, z)
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(-1.0, self.minimum, -1.0),
+            Point::new(1.0, self.maximum, 1.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod cylinder_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+    use crate::shape::shape_tests::ray;
+
+    fn truncated(closed: bool) -> Cylinder {
+        let mut cylinder = Cylinder::new();
+        cylinder.minimum = 1.0;
+        cylinder.maximum = 2.0;
+        cylinder.closed = closed;
+        cylinder
+    }
+
+    #[test]
+    fn test_default_cylinder() {
+        let cylinder = Cylinder::default();
+
+        assert_eq!(cylinder.minimum, f32::NEG_INFINITY);
+        assert_eq!(cylinder.maximum, f32::INFINITY);
+        assert!(!cylinder.closed);
+    }
+
+    #[test]
+    fn test_ray_misses_cylinder() {
+        let cylinder = Cylinder::new();
+        let cases = [
+            ((1.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 0.0, -5.0), (1.0, 1.0, 1.0)),
+        ];
+
+        for (origin, direction) in cases {
+            let xs = cylinder.local_intersect(&ray(origin, direction));
+            assert!(xs.is_empty(), "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_ray_hits_cylinder() {
+        let cylinder = Cylinder::new();
+        let cases = [
+            ((1.0, 0.0, -5.0), (0.0, 0.0, 1.0), 5.0, 5.0),
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 4.0, 6.0),
+            ((0.5, 0.0, -5.0), (0.1, 1.0, 1.0), 6.80798, 7.08872),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let xs = cylinder.local_intersect(&ray(origin, direction));
+            assert_eq!(xs.len(), 2);
+            assert!(compare_float(xs[0].t, t1), "{} != {}", xs[0].t, t1);
+            assert!(compare_float(xs[1].t, t2), "{} != {}", xs[1].t, t2);
+        }
+    }
+
+    #[test]
+    fn test_normal_on_cylinder() {
+        let cylinder = Cylinder::new();
+        let cases = [
+            ((1.0, 0.0, 0.0), (1.0, 0.0, 0.0)),
+            ((0.0, 5.0, -1.0), (0.0, 0.0, -1.0)),
+            ((0.0, -2.0, 1.0), (0.0, 0.0, 1.0)),
+            ((-1.0, 1.0, 0.0), (-1.0, 0.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            let found = cylinder.local_normal_at(Point::new(point.0, point.1, point.2));
+            assert!(found.is_same(&Vector::new(normal.0, normal.1, normal.2)));
+        }
+    }
+
+    #[test]
+    fn test_truncated_cylinder() {
+        let cylinder = truncated(false);
+        let cases = [
+            ((0.0, 1.5, 0.0), (0.1, 1.0, 0.0), 0),
+            ((0.0, 3.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 2.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 1.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 1.5, -2.0), (0.0, 0.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let xs = cylinder.local_intersect(&ray(origin, direction));
+            assert_eq!(xs.len(), count, "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_capped_cylinder() {
+        let cylinder = truncated(true);
+        let cases = [
+            ((0.0, 3.0, 0.0), (0.0, -1.0, 0.0), 2),
+            ((0.0, 3.0, -2.0), (0.0, -1.0, 2.0), 2),
+            // Down through the top cap and out of the very edge of the bottom one
+            ((0.0, 4.0, -2.0), (0.0, -1.0, 1.0), 2),
+            ((0.0, 0.0, -2.0), (0.0, 1.0, 2.0), 2),
+            ((0.0, -1.0, -2.0), (0.0, 1.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let xs = cylinder.local_intersect(&ray(origin, direction));
+            assert_eq!(xs.len(), count, "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_normal_on_caps() {
+        let cylinder = truncated(true);
+        let cases = [
+            ((0.0, 1.0, 0.0), (0.0, -1.0, 0.0)),
+            ((0.5, 1.0, 0.0), (0.0, -1.0, 0.0)),
+            ((0.0, 1.0, 0.5), (0.0, -1.0, 0.0)),
+            ((0.0, 2.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.5, 2.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 2.0, 0.5), (0.0, 1.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            let found = cylinder.local_normal_at(Point::new(point.0, point.1, point.2));
+            assert!(found.is_same(&Vector::new(normal.0, normal.1, normal.2)));
+        }
+    }
+
+    #[test]
+    fn test_cylinder_bounds() {
+        let bounds = truncated(true).bounds();
+
+        assert!(bounds.contains(&Point::new(1.0, 1.5, -1.0)));
+        assert!(!bounds.contains(&Point::new(0.0, 2.5, 0.0)));
+        assert!(Cylinder::new()
+            .bounds()
+            .contains(&Point::new(0.0, 1e30, 0.0)));
+    }
+
+    #[test]
+    fn test_normal_near_open_end() {
+        let mut cylinder = Cylinder::new();
+        cylinder.minimum = 0.0;
+        cylinder.maximum = 1.0;
+
+        // Just inside the rim of an open pipe is still the wall
+        let normal = cylinder.local_normal_at(Point::new(0.0, 0.995, -0.999_999_94));
+        assert!(normal.is_same(&Vector::new(0.0, 0.0, -1.0)));
+        let normal = cylinder.local_normal_at(Point::new(0.5, 0.005, 0.866));
+        assert!(normal.is_same(&Vector::new(0.5, 0.0, 0.866)));
+    }
+
+    #[test]
+    fn test_scaled_cylinder() {
+        // Scaling shrinks the direction in object space without changing its angle
+        let mut cylinder = Cylinder::new();
+        cylinder.minimum = -1.0;
+        cylinder.maximum = 1.0;
+        cylinder.closed = true;
+        cylinder.transform = Transform::new(Matrix4::scaling(200.0, 1.0, 200.0)).unwrap();
+
+        let xs = cylinder.intersect(&ray((-500.0, 0.0, 0.0), (1.0, 0.0, 0.0)));
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, 300.0));
+        assert!(compare_float(xs[1].t, 700.0));
+
+        cylinder.minimum = 0.0;
+        cylinder.transform = Transform::new(Matrix4::scaling(1.0, 200.0, 1.0)).unwrap();
+        let xs = cylinder.intersect(&ray((0.5, 300.0, 0.0), (0.0, -1.0, 0.0)));
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, 100.0));
+        assert!(compare_float(xs[1].t, 300.0));
+    }
+}
@@ -0,0 +1,425 @@
+//! Radiance RGBE images (.hdr), which keep the full range of the canvas' colors by storing an
+//! 8 bit mantissa for each channel and one exponent shared by the whole pixel
+use crate::prelude::*;
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Scanlines can only be run length encoded when their width fits in 15 bits, and short
+/// ones wouldn't gain anything from it
+const RLE_WIDTHS: Range<usize> = 8..0x8000;
+/// Repeats shorter than this are cheaper to write out as part of a literal
+const MIN_RUN: usize = 4;
+const MAX_RUN: usize = 127;
+const MAX_LITERAL: usize = 128;
+
+/// Packs a color into a shared exponent pixel. Negative channels are clamped to 0.
+fn to_rgbe(color: Color) -> [u8; 4] {
+    let [red, green, blue] =
+        [color.red, color.green, color.blue].map(|channel| channel.clamp(0.0, f32::MAX));
+    let largest = red.max(green).max(blue);
+    if largest < 1e-32 {
+        return [0; 4];
+    }
+
+    // The exponent puts the largest channel's mantissa in 0.5..1, like C's frexp
+    let mut exponent = largest.log2().floor() as i32 + 1;
+    if largest >= 2_f32.powi(exponent) {
+        exponent += 1;
+    }
+    let exponent = exponent.min(127);
+    let scale = 256.0 / 2_f32.powi(exponent);
+    [
+        (red * scale) as u8,
+        (green * scale) as u8,
+        (blue * scale) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+/// Unpacks a shared exponent pixel, placing each channel in the middle of the range its
+/// mantissa covers
+fn from_rgbe([red, green, blue, exponent]: [u8; 4]) -> Color {
+    if exponent == 0 {
+        return Color::zero();
+    }
+    let scale = 2_f32.powi(i32::from(exponent) - 136);
+    Color::new(
+        (f32::from(red) + 0.5) * scale,
+        (f32::from(green) + 0.5) * scale,
+        (f32::from(blue) + 0.5) * scale,
+    )
+}
+
+impl Canvas {
+    /// Streams the canvas out as a Radiance HDR image. Colors aren't clamped, so values above
+    /// 1.0 survive; scanlines wide enough to benefit are run length encoded.
+    pub fn write_hdr<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(
+            writer,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )?;
+
+        for row in &self.pixels {
+            let pixels: Vec<[u8; 4]> = row.iter().map(|pixel| to_rgbe(*pixel)).collect();
+            if RLE_WIDTHS.contains(&pixels.len()) {
+                writer.write_all(&[2, 2])?;
+                writer.write_all(&(pixels.len() as u16).to_be_bytes())?;
+                for channel in 0..4 {
+                    let bytes: Vec<u8> = pixels.iter().map(|pixel| pixel[channel]).collect();
+                    write_run_length_encoded(writer, &bytes)?;
+                }
+            } else {
+                for pixel in pixels {
+                    writer.write_all(&pixel)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_hdr(&self) -> Vec<u8> {
+        let mut hdr = Vec::new();
+        self.write_hdr(&mut hdr)
+            .expect("Writing to a Vec can't fail");
+        hdr
+    }
+}
+
+/// Writes one channel of a scanline as runs of a repeated byte (128 plus the run length,
+/// then the byte) and literal stretches (their length, then the bytes themselves)
+fn write_run_length_encoded<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let mut position = 0;
+    while position < bytes.len() {
+        let mut literal_end = position;
+        let mut run = 0;
+        while literal_end < bytes.len() {
+            run = run_length(&bytes[literal_end..]);
+            if run >= MIN_RUN {
+                break;
+            }
+            literal_end += 1;
+        }
+
+        for literal in bytes[position..literal_end].chunks(MAX_LITERAL) {
+            writer.write_all(&[literal.len() as u8])?;
+            writer.write_all(literal)?;
+        }
+        position = literal_end;
+        if position < bytes.len() {
+            writer.write_all(&[128 + run as u8, bytes[position]])?;
+            position += run;
+        }
+    }
+    Ok(())
+}
+
+fn run_length(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .take(MAX_RUN)
+        .take_while(|byte| **byte == bytes[0])
+        .count()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HdrError {
+    /// The header is missing its `#?` signature or has a line that can't be read
+    InvalidHeader(String),
+    /// Valid Radiance files this reader doesn't handle, like XYZE pixels or rotated images
+    Unsupported(String),
+    /// A run length encoded scanline that doesn't decode to exactly the image's width
+    InvalidScanline,
+    UnexpectedEnd,
+}
+
+impl fmt::Display for HdrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdrError::InvalidHeader(line) => write!(f, "Invalid HDR header line {:?}", line),
+            HdrError::Unsupported(feature) => write!(f, "Unsupported HDR: {}", feature),
+            HdrError::InvalidScanline => write!(f, "HDR scanline doesn't match the image width"),
+            HdrError::UnexpectedEnd => write!(f, "HDR data ended early"),
+        }
+    }
+}
+
+impl std::error::Error for HdrError {}
+
+impl Canvas {
+    /// Reads a Radiance HDR image with RGBE pixels, in either the run length encoded or the
+    /// flat scanline format. Header variables like EXPOSURE are ignored, so the colors are
+    /// exactly what the file stores.
+    pub fn from_hdr(data: &[u8]) -> Result<Canvas, HdrError> {
+        let mut reader = HdrReader { data, position: 0 };
+
+        let signature = reader.next_line()?;
+        if !signature.starts_with("#?") {
+            return Err(HdrError::InvalidHeader(signature.to_string()));
+        }
+        loop {
+            let line = reader.next_line()?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=") {
+                if format != "32-bit_rle_rgbe" {
+                    return Err(HdrError::Unsupported(format!("{} pixels", format)));
+                }
+            }
+        }
+
+        let resolution = reader.next_line()?;
+        let dimension = |token: &str| {
+            token
+                .parse::<i32>()
+                .ok()
+                .filter(|dimension| *dimension >= 0)
+                .ok_or_else(|| HdrError::InvalidHeader(resolution.to_string()))
+        };
+        let (width, height) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+            ["-Y", height, "+X", width] => (dimension(width)?, dimension(height)?),
+            [_, _, _, _] => {
+                return Err(HdrError::Unsupported(format!(
+                    "image orientation {:?}",
+                    resolution
+                )))
+            }
+            _ => return Err(HdrError::InvalidHeader(resolution.to_string())),
+        };
+
+        // Rows are decoded before building the canvas, so a header claiming a huge image
+        // with no data behind it fails instead of exhausting memory
+        let mut pixels = Vec::new();
+        for _ in 0..height {
+            pixels.push(reader.scanline(width as usize)?);
+        }
+        Ok(Canvas {
+            width,
+            height,
+            pixels,
+            alpha: None,
+        })
+    }
+}
+
+struct HdrReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> HdrReader<'a> {
+    fn next_line(&mut self) -> Result<&'a str, HdrError> {
+        let rest = &self.data[self.position..];
+        let length = rest
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .ok_or(HdrError::UnexpectedEnd)?;
+        self.position += length + 1;
+
+        let line = &rest[..length];
+        std::str::from_utf8(line)
+            .map(|line| line.trim_end_matches('\r'))
+            .map_err(|_| HdrError::InvalidHeader(String::from_utf8_lossy(line).into_owned()))
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], HdrError> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + count)
+            .ok_or(HdrError::UnexpectedEnd)?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    /// Run length encoded scanlines start with 2, 2 and the width, then hold each of the four
+    /// channels one after the other
+    fn scanline(&mut self, width: usize) -> Result<Vec<Color>, HdrError> {
+        let start = self
+            .data
+            .get(self.position..self.position + 4)
+            .ok_or(HdrError::UnexpectedEnd)?;
+        if !RLE_WIDTHS.contains(&width) || start[..2] != [2, 2] || start[2] & 0x80 != 0 {
+            return self.flat_scanline(width);
+        }
+        if usize::from(u16::from_be_bytes([start[2], start[3]])) != width {
+            return Err(HdrError::InvalidScanline);
+        }
+        self.position += 4;
+
+        let red = self.run_length_decoded(width)?;
+        let green = self.run_length_decoded(width)?;
+        let blue = self.run_length_decoded(width)?;
+        let exponent = self.run_length_decoded(width)?;
+        Ok((0..width)
+            .map(|x| from_rgbe([red[x], green[x], blue[x], exponent[x]]))
+            .collect())
+    }
+
+    fn run_length_decoded(&mut self, width: usize) -> Result<Vec<u8>, HdrError> {
+        let mut bytes = Vec::with_capacity(width);
+        while bytes.len() < width {
+            let count = usize::from(self.bytes(1)?[0]);
+            let length = if count > 128 { count - 128 } else { count };
+            if length == 0 || bytes.len() + length > width {
+                return Err(HdrError::InvalidScanline);
+            }
+
+            if count > 128 {
+                let value = self.bytes(1)?[0];
+                bytes.resize(bytes.len() + length, value);
+            } else {
+                bytes.extend_from_slice(self.bytes(length)?);
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Pixels one after the other, which may use the original format's runs: a pixel of
+    /// 1, 1, 1 repeats the previous one by its exponent byte, shifted up another 8 bits for
+    /// each run marker in a row
+    fn flat_scanline(&mut self, width: usize) -> Result<Vec<Color>, HdrError> {
+        let mut pixels = Vec::new();
+        let mut shift = 0;
+        while pixels.len() < width {
+            let pixel = self.bytes(4)?;
+            let pixel = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            if pixel[..3] != [1, 1, 1] {
+                pixels.push(pixel);
+                shift = 0;
+                continue;
+            }
+
+            let previous = *pixels.last().ok_or(HdrError::InvalidScanline)?;
+            let count = usize::from(pixel[3]) << shift;
+            if shift > 16 || pixels.len() + count > width {
+                return Err(HdrError::InvalidScanline);
+            }
+            pixels.resize(pixels.len() + count, previous);
+            shift += 8;
+        }
+        Ok(pixels.into_iter().map(from_rgbe).collect())
+    }
+}
+
+#[cfg(test)]
+mod hdr_tests {
+    use crate::hdr::*;
+
+    /// RGBE keeps 8 bits of precision relative to the brightest channel
+    fn is_close(a: Color, b: Color) -> bool {
+        let largest = b.red.max(b.green).max(b.blue).max(1e-6);
+        [a.red - b.red, a.green - b.green, a.blue - b.blue]
+            .iter()
+            .all(|difference| difference.abs() <= largest / 128.0)
+    }
+
+    #[test]
+    fn test_rgbe_conversion() {
+        assert_eq!(to_rgbe(Color::new(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+        assert_eq!(to_rgbe(Color::new(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color::new(-1.0, 0.0, 0.0)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color::new(0.0, 3.0, 0.0))[1..], [192, 0, 130]);
+
+        assert!(is_close(
+            from_rgbe([128, 64, 32, 129]),
+            Color::new(1.0, 0.5, 0.25)
+        ));
+        assert!(from_rgbe([0, 0, 0, 0]).is_same(&Color::zero()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for width in [3, 20] {
+            let mut canvas = Canvas::new(width, 4);
+            for y in 0..4 {
+                for x in 0..width {
+                    let brightness = 10_f32.powi(y - 2);
+                    let color = Color::new(x as f32, 0.5, 1.0) * brightness;
+                    canvas.write_pixel(x, y, color);
+                }
+            }
+
+            let read = Canvas::from_hdr(&canvas.to_hdr()).expect("Valid HDR");
+            assert_eq!(read.width, width);
+            assert_eq!(read.height, 4);
+            for y in 0..4 {
+                for x in 0..width {
+                    assert!(is_close(read.pixel_at(x, y), canvas.pixel_at(x, y)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_scanlines_are_run_length_encoded() {
+        let mut canvas = Canvas::new(300, 1);
+        for x in 0..300 {
+            canvas.write_pixel(x, 0, Color::new(2.0, 1.0, 0.0));
+        }
+
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 300\n";
+        let hdr = canvas.to_hdr();
+        assert_eq!(&hdr[..header.len()], header);
+        // Each channel is three runs of 127, 127 and 46 repeats
+        let red = [255, 128, 255, 128, 174, 128];
+        assert_eq!(hdr[header.len()..header.len() + 4], [2, 2, 1, 44]);
+        assert_eq!(hdr[header.len() + 4..header.len() + 10], red);
+        assert_eq!(hdr.len(), header.len() + 4 + 4 * 6);
+
+        let mut bytes = Vec::new();
+        write_run_length_encoded(&mut bytes, &[1, 2, 3, 3, 3, 3, 3, 4]).unwrap();
+        assert_eq!(bytes, [2, 1, 2, 133, 3, 1, 4]);
+    }
+
+    #[test]
+    fn test_reading_flat_scanlines_with_runs() {
+        let mut hdr = b"#?RGBE\n# made by hand\nEXPOSURE=2.0\n\n-Y 1 +X 5\n".to_vec();
+        hdr.extend([128, 64, 32, 129, 1, 1, 1, 3, 0, 0, 0, 0]);
+
+        let canvas = Canvas::from_hdr(&hdr).expect("Valid HDR");
+        for x in 0..4 {
+            assert!(is_close(canvas.pixel_at(x, 0), Color::new(1.0, 0.5, 0.25)));
+        }
+        assert!(canvas.pixel_at(4, 0).is_same(&Color::zero()));
+    }
+
+    #[test]
+    fn test_reading_malformed_hdr() {
+        let hdr = Canvas::new(10, 2).to_hdr();
+
+        assert_eq!(
+            Canvas::from_hdr(b"P6\n").err(),
+            Some(HdrError::InvalidHeader(String::from("P6")))
+        );
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n").err(),
+            Some(HdrError::Unsupported(String::from(
+                "32-bit_rle_xyze pixels"
+            )))
+        );
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n+Y 1 +X 1\n").err(),
+            Some(HdrError::Unsupported(String::from(
+                "image orientation \"+Y 1 +X 1\""
+            )))
+        );
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n-Y one +X 1\n").err(),
+            Some(HdrError::InvalidHeader(String::from("-Y one +X 1")))
+        );
+        assert_eq!(
+            Canvas::from_hdr(&hdr[..hdr.len() - 1]).err(),
+            Some(HdrError::UnexpectedEnd)
+        );
+
+        let mut corrupt = hdr.clone();
+        let first_scanline = hdr.len() - 2 * (4 + 4 * 2);
+        corrupt[first_scanline + 4] = 128 + 11;
+        assert_eq!(
+            Canvas::from_hdr(&corrupt).err(),
+            Some(HdrError::InvalidScanline)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::prelude::*;
+use std::ops::Index;
+
+#[derive(Copy, Clone)]
+pub struct Intersection<'a> {
+    pub t: f32,
+    pub object: &'a dyn Shape,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(t: f32, object: &'a dyn Shape) -> Self {
+        Self { t, object }
+    }
+
+    /// Works out everything needed to shade the intersection that `ray` made
+    pub fn prepare_computations(&self, ray: &Ray) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at(point);
+
+        // When the eye is inside the object the normal points away from it, so flip it
+        let inside = normalv.dot_product(&eyev) < 0.0;
+        if inside {
+            normalv = -normalv;
+        }
+
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            over_point: point + normalv * EPSILON,
+            eyev,
+            normalv,
+            inside,
+        }
+    }
+}
+
+/// The state of an intersection that shading needs, found once up front
+pub struct Computations<'a> {
+    pub t: f32,
+    pub object: &'a dyn Shape,
+    /// Where the ray hit the object, in world space
+    pub point: Point,
+    /// `point` nudged off the surface along the normal. Rounding can leave `point` just
+    /// below the surface, where it would shadow itself and cause acne.
+    pub over_point: Point,
+    /// Points back towards the eye
+    pub eyev: Vector,
+    /// The surface normal, flipped if needed so it faces the eye
+    pub normalv: Vector,
+    /// Whether the ray started inside the object
+    pub inside: bool,
+}
+
+/// A collection of intersections, always kept sorted by t
+pub struct Intersections<'a> {
+    list: Vec<Intersection<'a>>,
+}
+
+impl<'a> Intersections<'a> {
+    pub fn new(mut list: Vec<Intersection<'a>>) -> Self {
+        list.sort_by(|a, b| a.t.total_cmp(&b.t));
+        Self { list }
+    }
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+    /// # Returns
+    /// The visible intersection, i.e. the one with the lowest non-negative t
+    pub fn hit(&self) -> Option<&Intersection<'a>> {
+        self.list.iter().find(|intersection| intersection.t >= 0.0)
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, Intersection<'a>> {
+        self.list.iter()
+    }
+}
+
+impl<'a> IntoIterator for Intersections<'a> {
+    type Item = Intersection<'a>;
+    type IntoIter = std::vec::IntoIter<Intersection<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+impl<'a> FromIterator<Intersection<'a>> for Intersections<'a> {
+    fn from_iter<I: IntoIterator<Item = Intersection<'a>>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<'a> Index<usize> for Intersections<'a> {
+    type Output = Intersection<'a>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.list[index]
+    }
+}
+
+#[cfg(test)]
+mod intersections_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_intersections_are_sorted() {
+        let sphere = Sphere::new();
+        let xs = Intersections::new(vec![
+            Intersection::new(5.0, &sphere),
+            Intersection::new(-3.0, &sphere),
+            Intersection::new(2.0, &sphere),
+        ]);
+
+        assert_eq!(xs.len(), 3);
+        assert_eq!(xs[0].t, -3.0);
+        assert_eq!(xs[1].t, 2.0);
+        assert_eq!(xs[2].t, 5.0);
+    }
+
+    #[test]
+    fn test_hit_all_positive() {
+        let sphere = Sphere::new();
+        let xs = Intersections::new(vec![
+            Intersection::new(1.0, &sphere),
+            Intersection::new(2.0, &sphere),
+        ]);
+
+        assert_eq!(xs.hit().expect("Both intersections are visible").t, 1.0);
+    }
+
+    #[test]
+    fn test_hit_some_negative() {
+        let sphere = Sphere::new();
+        let xs = Intersections::new(vec![
+            Intersection::new(-1.0, &sphere),
+            Intersection::new(1.0, &sphere),
+        ]);
+
+        assert_eq!(xs.hit().expect("One intersection is visible").t, 1.0);
+    }
+
+    #[test]
+    fn test_hit_all_negative() {
+        let sphere = Sphere::new();
+        let xs = Intersections::new(vec![
+            Intersection::new(-2.0, &sphere),
+            Intersection::new(-1.0, &sphere),
+        ]);
+
+        assert!(xs.hit().is_none());
+    }
+
+    #[test]
+    fn test_hit_is_lowest_non_negative() {
+        let sphere = Sphere::new();
+        let xs = Intersections::new(vec![
+            Intersection::new(5.0, &sphere),
+            Intersection::new(7.0, &sphere),
+            Intersection::new(-3.0, &sphere),
+            Intersection::new(2.0, &sphere),
+        ]);
+
+        let hit = xs.hit().expect("Three intersections are visible");
+        assert_eq!(hit.t, 2.0);
+        assert!(std::ptr::addr_eq(hit.object, &sphere));
+    }
+
+    #[test]
+    fn test_collecting_intersections() {
+        let sphere = Sphere::new();
+        let xs: Intersections = [3.0, -1.0, 2.0]
+            .into_iter()
+            .map(|t| Intersection::new(t, &sphere))
+            .collect();
+
+        let ts: Vec<f32> = xs.iter().map(|intersection| intersection.t).collect();
+        assert_eq!(ts, [-1.0, 2.0, 3.0]);
+        assert_eq!(xs.into_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_precomputing_intersection_state() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+        let comps = Intersection::new(4.0, &sphere).prepare_computations(&ray);
+
+        assert_eq!(comps.t, 4.0);
+        assert!(std::ptr::addr_eq(comps.object, &sphere));
+        assert!(comps.point.compare(&Point::new(0.0, 0.0, -1.0)));
+        assert!(comps.eyev.is_same(&Vector::new(0.0, 0.0, -1.0)));
+        assert!(comps.normalv.is_same(&Vector::new(0.0, 0.0, -1.0)));
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn test_hit_from_inside() {
+        let ray = Ray::new(Point::zero(), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+        let comps = Intersection::new(1.0, &sphere).prepare_computations(&ray);
+
+        assert!(comps.point.compare(&Point::new(0.0, 0.0, 1.0)));
+        assert!(comps.eyev.is_same(&Vector::new(0.0, 0.0, -1.0)));
+        assert!(comps.inside);
+        // The normal is flipped to face the eye
+        assert!(comps.normalv.is_same(&Vector::new(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn test_hit_offsets_point() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut sphere = Sphere::new();
+        sphere.transform = Transform::new(Matrix4::translation(0.0, 0.0, 1.0)).unwrap();
+        let comps = Intersection::new(5.0, &sphere).prepare_computations(&ray);
+
+        assert!(comps.over_point.position.z < -EPSILON / 2.0);
+        assert!(comps.point.position.z > comps.over_point.position.z);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::prelude::*;
+
+/// A light with no size that shines equally in every direction
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointLight {
+    pub position: Point,
+    pub intensity: Color,
+}
+
+impl PointLight {
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod light_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_creating_point_light() {
+        let light = PointLight::new(Point::zero(), Color::WHITE);
+
+        assert!(light.position.compare(&Point::zero()));
+        assert_eq!(light.intensity, Color::WHITE);
+    }
+}
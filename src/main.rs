@@ -1,46 +1,148 @@
 #![forbid(clippy::all)]
 #![allow(unused_assignments, unused_imports)]
+mod bmp;
+mod camera;
 mod canvas;
 mod color;
+mod cone;
+mod cube;
+mod cylinder;
+mod hdr;
+mod intersections;
+mod light;
+mod material;
 mod matrix;
+mod pfm;
+mod plane;
+mod png;
 mod points;
+mod polynomial;
+mod ppm;
+mod ray;
+mod shape;
+mod spectrum;
+mod sphere;
+mod tga;
+mod tonemap;
+mod torus;
+mod world;
+mod zlib;
 
 mod prelude {
     #![allow(unused_imports)]
+    pub use crate::camera::*;
     pub use crate::canvas::*;
     pub use crate::color::*;
+    pub use crate::cone::*;
+    pub use crate::cube::*;
+    pub use crate::cylinder::*;
+    pub use crate::intersections::*;
+    pub use crate::light::*;
+    pub use crate::material::*;
+    pub use crate::matrix::*;
+    pub use crate::plane::*;
     pub use crate::points::*;
+    pub use crate::ppm::*;
+    pub use crate::ray::*;
+    pub use crate::shape::*;
+    pub use crate::sphere::*;
+    pub use crate::tga::*;
+    pub use crate::tonemap::*;
+    pub use crate::torus::*;
+    pub use crate::world::*;
     pub const EPSILON: f32 = 0.01;
 }
 
 use prelude::*;
-use std::fs;
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::BufWriter;
 
-fn main() {}
+fn main() {
+    write_canvas_to_file(&render_scene());
+}
 
-fn write_canvas_to_file(canvas: &Canvas) {
-    fs::write("./output/image.ppm", canvas.to_pmm()).expect("Unable to write to file");
+/// Three spheres sitting on a floor in front of two walls
+fn render_scene() -> Canvas {
+    let transform = |matrix| Transform::new(matrix).expect("Scene transforms are invertible");
+    let mut wall_material = Material::new();
+    wall_material.color = Color::new(1.0, 0.9, 0.9);
+    wall_material.specular = 0.0;
+
+    let mut floor = Plane::new();
+    floor.material = wall_material;
+
+    let mut left_wall = Plane::new();
+    left_wall.transform = transform(
+        Matrix4::from_identity()
+            .rotate_x(PI / 2.0)
+            .rotate_y(-PI / 4.0)
+            .translate(0.0, 0.0, 5.0),
+    );
+    left_wall.material = wall_material;
+
+    let mut right_wall = Plane::new();
+    right_wall.transform = transform(
+        Matrix4::from_identity()
+            .rotate_x(PI / 2.0)
+            .rotate_y(PI / 4.0)
+            .translate(0.0, 0.0, 5.0),
+    );
+    right_wall.material = wall_material;
+
+    let mut middle = Sphere::new();
+    middle.transform = transform(Matrix4::translation(-0.5, 1.0, 0.5));
+    middle.material.color = Color::new(0.1, 1.0, 0.5);
+    middle.material.diffuse = 0.7;
+    middle.material.specular = 0.3;
+
+    let mut right = Sphere::new();
+    right.transform = transform(Matrix4::scaling(0.5, 0.5, 0.5).translate(1.5, 0.5, -0.5));
+    right.material.color = Color::new(0.5, 1.0, 0.1);
+    right.material.diffuse = 0.7;
+    right.material.specular = 0.3;
+
+    let mut left = Sphere::new();
+    left.transform = transform(Matrix4::scaling(0.33, 0.33, 0.33).translate(-1.5, 0.33, -0.75));
+    left.material.color = Color::new(1.0, 0.8, 0.1);
+    left.material.diffuse = 0.7;
+    left.material.specular = 0.3;
+
+    let world = World {
+        objects: vec![
+            Box::new(floor),
+            Box::new(left_wall),
+            Box::new(right_wall),
+            Box::new(middle),
+            Box::new(right),
+            Box::new(left),
+        ],
+        lights: vec![PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::WHITE,
+        )],
+    };
+
+    let mut camera = Camera::new(400, 200, PI / 3.0);
+    camera.transform = transform(view_transform(
+        &Point::new(0.0, 1.5, -5.0),
+        &Point::new(0.0, 1.0, 0.0),
+        &Vector::new(0.0, 1.0, 0.0),
+    ));
+    camera.render(&world)
 }
 
-fn draw_projectile() {
-    let mut canvas = Canvas::new(800, 400);
-    let mut point = Vector::new(0.1, 1.0, 0.0);
-    let mut velocity = Vector::new(1.0, 1.8, 0.6);
-    velocity.normalize();
-    velocity.scale(9.25);
-    let gravity = Vector::new(0.0, -0.1, 0.0);
-    let wind = Vector::new(-0.01, 0.0, 0.0);
-
-    while point.y > 0.0 {
-        point.apply(&velocity);
-        velocity.apply(&gravity);
-        velocity.apply(&wind);
-
-        let canvas_y = canvas.height - point.y as i32;
-        canvas.write_pixel(point.x as i32, point.z as i32, Color::from_green());
-        println!("X:{} Y:{}", point.x, point.y);
-    }
-    write_canvas_to_file(&canvas);
+fn write_canvas_to_file(canvas: &Canvas) {
+    let canvas = canvas.post_processed(&PostProcess::default());
+    let file = File::create("./output/image.ppm").expect("Unable to create file");
+    canvas
+        .write_ppm(&mut BufWriter::new(file), PpmFormat::Plain, 255)
+        .expect("Unable to write to file");
+
+    let file = File::create("./output/image.png").expect("Unable to create file");
+    canvas
+        .write_png(&mut BufWriter::new(file))
+        .expect("Unable to write to file");
 }
 /// # Returns
 /// Returns true if a == b within crate::EPSILON
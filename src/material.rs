@@ -0,0 +1,153 @@
+use crate::prelude::*;
+
+/// The surface properties used by the Phong reflection model
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Material {
+    pub color: Color,
+    /// Light reflected from the surroundings, lighting even the parts a light can't see
+    pub ambient: f32,
+    /// Light scattered evenly off a matte surface
+    pub diffuse: f32,
+    /// The brightness of the highlight off a shiny surface
+    pub specular: f32,
+    /// How small and tight the highlight is, from about 10 (very large) to 200 (very small)
+    pub shininess: f32,
+}
+
+impl Material {
+    pub fn new() -> Self {
+        Self {
+            color: Color::WHITE,
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shades a point with the Phong reflection model, adding together the ambient, diffuse and
+/// specular light it receives from `light`.
+/// `eyev` points from the point towards the eye and `normalv` is the surface normal, both
+/// normalized. A point `in_shadow` only gets the ambient light.
+pub fn lighting(
+    material: &Material,
+    light: &PointLight,
+    point: Point,
+    eyev: Vector,
+    normalv: Vector,
+    in_shadow: bool,
+) -> Color {
+    let effective_color = material.color * light.intensity;
+    let mut lightv = light.position - point;
+    lightv.normalize();
+
+    let ambient = effective_color * material.ambient;
+    // A negative cosine means the light is on the other side of the surface
+    let light_dot_normal = lightv.dot_product(&normalv);
+    if in_shadow || light_dot_normal < 0.0 {
+        return ambient;
+    }
+    let diffuse = effective_color * material.diffuse * light_dot_normal;
+
+    // A negative cosine means the light reflects away from the eye
+    let reflect_dot_eye = (-lightv).reflect(&normalv).dot_product(&eyev);
+    let specular = if reflect_dot_eye <= 0.0 {
+        Color::BLACK
+    } else {
+        light.intensity * material.specular * reflect_dot_eye.powf(material.shininess)
+    };
+    ambient + diffuse + specular
+}
+
+#[cfg(test)]
+mod material_tests {
+    use crate::prelude::*;
+
+    fn lighting_setup() -> (Material, Point) {
+        (Material::new(), Point::zero())
+    }
+
+    #[test]
+    fn test_default_material() {
+        let material = Material::default();
+
+        assert_eq!(material.color, Color::WHITE);
+        assert_eq!(material.ambient, 0.1);
+        assert_eq!(material.diffuse, 0.9);
+        assert_eq!(material.specular, 0.9);
+        assert_eq!(material.shininess, 200.0);
+    }
+
+    #[test]
+    fn test_lighting_with_eye_between_light_and_surface() {
+        let (material, position) = lighting_setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE);
+
+        let result = lighting(&material, &light, position, eyev, normalv, false);
+        assert!(result.is_same(&Color::new(1.9, 1.9, 1.9)));
+    }
+
+    #[test]
+    fn test_lighting_with_eye_offset_45_degrees() {
+        let (material, position) = lighting_setup();
+        let eyev = Vector::new(0.0, f32::sqrt(2.0) / 2.0, -f32::sqrt(2.0) / 2.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE);
+
+        let result = lighting(&material, &light, position, eyev, normalv, false);
+        assert!(result.is_same(&Color::new(1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_lighting_with_light_offset_45_degrees() {
+        let (material, position) = lighting_setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::WHITE);
+
+        let result = lighting(&material, &light, position, eyev, normalv, false);
+        assert!(result.is_same(&Color::new(0.7364, 0.7364, 0.7364)));
+    }
+
+    #[test]
+    fn test_lighting_with_eye_in_path_of_reflection() {
+        let (material, position) = lighting_setup();
+        let eyev = Vector::new(0.0, -f32::sqrt(2.0) / 2.0, -f32::sqrt(2.0) / 2.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::WHITE);
+
+        let result = lighting(&material, &light, position, eyev, normalv, false);
+        assert!(result.is_same(&Color::new(1.6364, 1.6364, 1.6364)));
+    }
+
+    #[test]
+    fn test_lighting_with_light_behind_surface() {
+        let (material, position) = lighting_setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::WHITE);
+
+        let result = lighting(&material, &light, position, eyev, normalv, false);
+        assert!(result.is_same(&Color::new(0.1, 0.1, 0.1)));
+    }
+
+    #[test]
+    fn test_lighting_with_surface_in_shadow() {
+        let (material, position) = lighting_setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE);
+
+        let result = lighting(&material, &light, position, eyev, normalv, true);
+        assert!(result.is_same(&Color::new(0.1, 0.1, 0.1)));
+    }
+}
@@ -1,296 +1,428 @@
 use crate::prelude::*;
-use std::fmt::Error;
+use std::fmt;
+use std::ops::Mul;
 
-#[derive(Debug, Clone)]
-struct Matrix {
-    pub points: Vec<Vec<f32>>,
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The matrix has a determinant of zero, so it has no inverse
+    Singular,
 }
 
-impl Matrix {
-    pub fn new(rows: i32, columns: i32) -> Self {
-        Self {
-            points: vec![vec![0.0; columns as usize]; rows as usize],
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::Singular => write!(f, "Cannot invert a singular matrix"),
         }
     }
-    //TODO roll these into one function that takes a row of and just dynamically make the vecs instead
-    pub fn new_4x4_from_rows(
-        row1: Vec<f32>,
-        row2: Vec<f32>,
-        row3: Vec<f32>,
-        row4: Vec<f32>,
-    ) -> Self {
-        let mut matrix = Self::new(4, 4);
-        matrix.points[0] = row1;
-        matrix.points[1] = row2;
-        matrix.points[2] = row3;
-        matrix.points[3] = row4;
+}
 
-        matrix
-    }
-    pub fn new_3x3_from_rows(row1: Vec<f32>, row2: Vec<f32>, row3: Vec<f32>) -> Self {
-        let mut matrix = Self::new(3, 3);
-        matrix.points[0] = row1;
-        matrix.points[1] = row2;
-        matrix.points[2] = row3;
-        matrix
-    }
-    pub fn new_2x2_from_rows(row1: Vec<f32>, row2: Vec<f32>) -> Self {
-        let mut matrix = Self::new(2, 2);
-        matrix.points[0] = row1;
-        matrix.points[1] = row2;
+impl std::error::Error for MatrixError {}
 
-        matrix
+/// A square matrix of size N, stored on the stack so it can be copied around freely
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Matrix<const N: usize> {
+    pub points: [[f32; N]; N],
+}
+
+pub type Matrix4 = Matrix<4>;
+pub type Matrix3 = Matrix<3>;
+pub type Matrix2 = Matrix<2>;
+
+impl<const N: usize> Matrix<N> {
+    pub fn new() -> Self {
+        Self {
+            points: [[0.0; N]; N],
+        }
+    }
+    pub fn from_rows(rows: [[f32; N]; N]) -> Self {
+        Self { points: rows }
     }
     pub fn from_identity() -> Self {
-        Matrix::new_4x4_from_rows(
-            vec![1.0, 0.0, 0.0, 0.0],
-            vec![0.0, 1.0, 0.0, 0.0],
-            vec![0.0, 0.0, 1.0, 0.0],
-            vec![0.0, 0.0, 0.0, 1.0],
-        )
+        let mut matrix = Self::new();
+        for (x, row) in matrix.points.iter_mut().enumerate() {
+            row[x] = 1.0;
+        }
+        matrix
     }
-    pub fn multiply_matrices(first: &Matrix, second: &Matrix) -> Matrix {
-        let mut new_matrix = Matrix::new(first.points.len() as i32, first.points[0].len() as i32);
+    pub fn multiply_matrices(first: &Matrix<N>, second: &Matrix<N>) -> Matrix<N> {
+        let mut new_matrix = Matrix::new();
 
-        for x in 0..first.points.len() {
-            for y in 0..first.points[0].len() {
-                new_matrix.points[x][y] = first.points[x][0] * second.points[0][y]
-                    + first.points[x][1] * second.points[1][y]
-                    + first.points[x][2] * second.points[2][y]
-                    + first.points[x][3] * second.points[3][y];
+        for (x, row) in new_matrix.points.iter_mut().enumerate() {
+            for (y, point) in row.iter_mut().enumerate() {
+                *point = (0..N)
+                    .map(|i| first.points[x][i] * second.points[i][y])
+                    .sum();
             }
         }
 
         new_matrix
     }
-    pub fn transposed_matrix(matrix: &Matrix) -> Self {
-        let mut new_matrix = Matrix::new(matrix.points.len() as i32, matrix.points[0].len() as i32);
-        for x in 0..new_matrix.points.len() {
-            for y in 0..new_matrix.points[0].len() {
-                new_matrix.points[x][y] = matrix.points[y][x];
+    pub fn transposed_matrix(matrix: &Matrix<N>) -> Self {
+        let mut new_matrix = Matrix::new();
+        for (x, row) in new_matrix.points.iter_mut().enumerate() {
+            for (y, point) in row.iter_mut().enumerate() {
+                *point = matrix.points[y][x];
             }
         }
         new_matrix
     }
-    fn multiply_matrix_row(row: &Vec<f32>, other_row: &Vec<f32>) -> f32 {
-        let mut total = 0.0;
-
-        for x in 0..row.len() {
-            total += row[x] * other_row[x];
+    fn multiply_matrix_row(row: &[f32; N], other_row: &[f32; N]) -> f32 {
+        row.iter().zip(other_row).map(|(a, b)| a * b).sum()
+    }
+    pub fn transpose(&mut self) {
+        *self = Self::transposed_matrix(self);
+    }
+    pub fn transposed(&self) -> Self {
+        Self::transposed_matrix(self)
+    }
+    pub fn multiply_by_matrix(&self, matrix: &Matrix<N>) -> Matrix<N> {
+        Self::multiply_matrices(self, matrix)
+    }
+    pub fn equals(&self, other: &Matrix<N>) -> bool {
+        for (row, other_row) in self.points.iter().zip(other.points.iter()) {
+            for (point, other_point) in row.iter().zip(other_row) {
+                if !crate::compare_float(*other_point, *point) {
+                    return false;
+                }
+            }
         }
+        true
+    }
+}
 
-        total
-    }
-    fn submatrix(
-        matrix: &Matrix,
-        row_to_delete: i32,
-        column_to_delete: i32,
-    ) -> Result<Matrix, &str> {
-        if row_to_delete > matrix.points.len() as i32
-            || column_to_delete > matrix.points[0].len() as i32
-        {
-            return Err("Cannot delete a row that doesn't exist");
-        }
+impl<const N: usize> Matrix<N> {
+    /// Reduces the matrix to the identity using Gauss-Jordan elimination with partial pivoting,
+    /// applying the same row operations to `augmented`.
+    /// # Returns
+    /// The reduced `augmented` rows along with the determinant of the matrix
+    fn gauss_jordan<const M: usize>(
+        &self,
+        mut augmented: [[f32; M]; N],
+    ) -> Result<([[f32; M]; N], f32), MatrixError> {
+        let mut points = self.points;
+        let mut determinant = 1.0;
 
-        let mut new_matrix = Matrix::new(0, 0);
-
-        //  fuck
-        for x in 0..matrix.points.len() {
-            if x != row_to_delete as usize {
-                new_matrix.points.push(vec![]);
-                for y in 0..matrix.points[0].len() {
-                    if y != column_to_delete as usize {
-                        new_matrix
-                            .points
-                            .last_mut()
-                            .unwrap()
-                            .push(matrix.points[x][y])
-                    }
+        let largest = points
+            .iter()
+            .flatten()
+            .fold(0.0_f32, |max, point| max.max(point.abs()));
+        let tolerance = largest * N as f32 * f32::EPSILON;
+
+        for column in 0..N {
+            // Dividing by the largest remaining value in the column keeps the rounding error down
+            let pivot_row = (column..N)
+                .max_by(|a, b| {
+                    points[*a][column]
+                        .abs()
+                        .total_cmp(&points[*b][column].abs())
+                })
+                .expect("Range always contains the current column");
+            let pivot = points[pivot_row][column];
+            if pivot.abs() <= tolerance {
+                return Err(MatrixError::Singular);
+            }
+            if pivot_row != column {
+                points.swap(pivot_row, column);
+                augmented.swap(pivot_row, column);
+                determinant = -determinant;
+            }
+            determinant *= pivot;
+
+            points[column].iter_mut().for_each(|point| *point /= pivot);
+            augmented[column]
+                .iter_mut()
+                .for_each(|point| *point /= pivot);
+            let pivot_points = points[column];
+            let pivot_augmented = augmented[column];
+
+            let rows = points.iter_mut().zip(augmented.iter_mut()).enumerate();
+            for (row, (points_row, augmented_row)) in rows {
+                let factor = points_row[column];
+                if row == column || factor == 0.0 {
+                    continue;
+                }
+                for (point, pivot_point) in points_row.iter_mut().zip(pivot_points) {
+                    *point -= factor * pivot_point;
+                }
+                for (point, pivot_point) in augmented_row.iter_mut().zip(pivot_augmented) {
+                    *point -= factor * pivot_point;
                 }
             }
         }
-        Ok(new_matrix)
-    }
-    fn minor(matrix: &Matrix, row: i32, column: i32) -> Result<f32, &str> {
-        if matrix.points.len() >= 3 && matrix.points[0].len() >= 3 {
-            let submatrix = Matrix::submatrix(matrix, row, column)?;
-            Ok(submatrix.get_determinant())
-        } else {
-            Err("Matrix must be larger than 3x3 ")
+
+        Ok((augmented, determinant))
+    }
+    pub fn determinant(&self) -> f32 {
+        match self.gauss_jordan([[0.0; 0]; N]) {
+            Ok((_, determinant)) => determinant,
+            Err(MatrixError::Singular) => 0.0,
         }
     }
+    pub fn inverse(&self) -> Result<Matrix<N>, MatrixError> {
+        let (inverse, _) = self.gauss_jordan(Self::from_identity().points)?;
+        Ok(Self::from_rows(inverse))
+    }
+    /// # Returns
+    /// The x that satisfies `self * x = b`
+    pub fn solve(&self, b: [f32; N]) -> Result<[f32; N], MatrixError> {
+        let (solution, _) = self.gauss_jordan(b.map(|value| [value]))?;
+        Ok(solution.map(|[value]| value))
+    }
+    pub fn is_invertible(&self) -> bool {
+        self.gauss_jordan([[0.0; 0]; N]).is_ok()
+    }
+}
 
-    fn cofactor_3x3(matrix: &Matrix, row: i32, column: i32) -> Result<f32, &str> {
-        if matrix.points.len() != 3 && matrix.points[0].len() != 3 {
-            return Err("Must be a 3x3 matrix");
-        }
-        let is_even = (row + column) % 2 == 0;
-        let minor = Matrix::minor(matrix, row, column)?;
+impl<const N: usize> Default for Matrix<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        if is_even {
-            Ok(minor)
-        } else {
-            Ok(-minor)
-        }
+impl<const N: usize> Mul for Matrix<N> {
+    type Output = Matrix<N>;
+
+    fn mul(self, rhs: Matrix<N>) -> Self::Output {
+        Matrix::multiply_matrices(&self, &rhs)
     }
+}
 
-    fn cofactor_4x4(matrix: &Matrix, row: i32, column: i32) -> Result<f32, &str> {
-        if matrix.points.len() != 4 || matrix.points[0].len() != 4 {
-            return Err("Must be a 4x4 matrix");
-        }
+impl Mul<Tuple4> for Matrix4 {
+    type Output = Tuple4;
+
+    fn mul(self, rhs: Tuple4) -> Self::Output {
+        self.multiply_by_tuple4(&rhs)
+    }
+}
 
-        let is_even = (row + column) % 2 == 0;
-        let minor = Matrix::minor(matrix, row, column)?;
+impl Mul<Point> for Matrix4 {
+    type Output = Point;
 
-        if is_even {
-            Ok(minor)
-        } else {
-            Ok(-minor)
-        }
+    fn mul(self, rhs: Point) -> Self::Output {
+        self.multiply_by_point(&rhs)
     }
-    
-    fn inverse(matrix: &Matrix) -> Result<Matrix,&str>{
-        let length = matrix.points.len();
-        if !matrix.is_invertible() { return Err("Cannot invert this matrix")};
-        if length != 4 { return Err("Only 4x4 Matrices for now") }
-        let mut new_matrix = Matrix::new(matrix.points.len() as i32,matrix.points[0].len() as i32);
-        
-        for row in 0.. length{
-            for column in 0..new_matrix.points[0].len(){
-                let cofactor = 
-                    Matrix::cofactor_4x4(&matrix, row as i32,column as i32).expect("Err logically unreachable");
-                // Col / Row flipped for transposition.
-                new_matrix.points[column][row] = cofactor / Matrix::get_determinant(matrix);
-            }
-        } 
-        
-        Ok(new_matrix)
-        
-    }
-    pub fn set_to(&mut self, matrix: &Matrix) {
-        for x in 0..self.points.len() {
-            for y in 0..self.points[0].len() {
-                self.points[x][y] = matrix.points[x][y];
-            }
-        }
+}
+
+impl Mul<Vector> for Matrix4 {
+    type Output = Vector;
+
+    fn mul(self, rhs: Vector) -> Self::Output {
+        self.multiply_by_vector(&rhs)
     }
-    pub fn transpose(&mut self) {
-        self.set_to(&Self::transposed_matrix(&self));
+}
+
+impl Matrix4 {
+    pub fn translation(x: f32, y: f32, z: f32) -> Self {
+        Matrix::from_rows([
+            [1.0, 0.0, 0.0, x],
+            [0.0, 1.0, 0.0, y],
+            [0.0, 0.0, 1.0, z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
     }
-    pub fn transposed(&self) -> Self {
-        Self::transposed_matrix(self)
+    pub fn scaling(x: f32, y: f32, z: f32) -> Self {
+        Matrix::from_rows([
+            [x, 0.0, 0.0, 0.0],
+            [0.0, y, 0.0, 0.0],
+            [0.0, 0.0, z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
     }
-    pub fn get_determinant(&self) -> f32 {
-        // This is horrendous. probably worth refactoring cofactor to read what size the matrix is/ making all functions matrix size agnostic
-        if self.points.len() == 2 && self.points[0].len() == 2 {
-            self.points[0][0] * self.points[1][1] - (self.points[0][1] * self.points[1][0])
-        } else {
-            if self.points.len() == 3 && self.points[0].len() == 3 {
-                let mut determinant = 0.0;
-                for x in 0..self.points[0].len() {
-                    let cofactor = Self::cofactor_3x3(&self, 0, x as i32).expect("Err is logically unreachable");
-                    determinant += self.points[0][x] * cofactor;
-                }
-                determinant
-            } else {
-                {
-                    let mut determinant = 0.0;
-                    for x in 0..self.points[0].len() {
-                        let cofactor = Self::cofactor_4x4(&self, 0, x as i32).expect("Err is logically unreachable");
-                        determinant += self.points[0][x] * cofactor;
-                    }
-                    determinant
-                }
-            }
-        }
+    /// Rotation around the x axis, `radians` clockwise when looking down the axis towards the origin
+    pub fn rotation_x(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Matrix::from_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, -sin, 0.0],
+            [0.0, sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
     }
-    pub fn multiply_by_matrix(&self, matrix: &Matrix) -> Matrix {
-        if self.points.len() != matrix.points.len() {
-            panic!("Matrices are not of same length")
-        };
-        Self::multiply_matrices(&self, matrix)
-    }
-    pub fn multiply_by_point(&self, point: &Point) -> (Point, f32) {
-        if self.points.len() != 4 {
-            panic!("Do not multiply a non 4x4 Matrix by a point")
-        };
-        let mut new_point = Point::zero();
-
-        let point_row = vec![point.position.x, point.position.y, point.position.z, 1.0];
-        new_point.position.x = Self::multiply_matrix_row(&self.points[0], &point_row);
-        new_point.position.y = Self::multiply_matrix_row(&self.points[1], &point_row);
-        new_point.position.z = Self::multiply_matrix_row(&self.points[2], &point_row);
-
-        (
-            new_point,
-            Self::multiply_matrix_row(&self.points[3], &vec![1.0; 4]),
-        )
+    pub fn rotation_y(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Matrix::from_rows([
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
     }
-    pub fn multiply_by_vector(&self, vector: &Vector) -> (Vector, f32) {
-        if self.points.len() != 4 {
-            panic!("Do not multiply a non 4x4 Matrix by a vector")
-        };
-        let mut new_vector = Vector::zero();
-
-        let point_row = vec![vector.x, vector.y, vector.z, 1.0];
-        new_vector.x = Self::multiply_matrix_row(&self.points[0], &point_row);
-        new_vector.y = Self::multiply_matrix_row(&self.points[1], &point_row);
-        new_vector.z = Self::multiply_matrix_row(&self.points[2], &point_row);
+    pub fn rotation_z(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Matrix::from_rows([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// Each component moves in proportion to the other two,
+    /// e.g. `x_y` is how much x moves in proportion to y
+    pub fn shearing(x_y: f32, x_z: f32, y_x: f32, y_z: f32, z_x: f32, z_y: f32) -> Self {
+        Matrix::from_rows([
+            [1.0, x_y, x_z, 0.0],
+            [y_x, 1.0, y_z, 0.0],
+            [z_x, z_y, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    // Fluent versions of the builders above. Each one is applied after the transforms already
+    // in the chain, so `from_identity().rotate_x(..).scale(..)` rotates first and then scales.
+    pub fn translate(self, x: f32, y: f32, z: f32) -> Self {
+        Matrix::translation(x, y, z) * self
+    }
+    pub fn scale(self, x: f32, y: f32, z: f32) -> Self {
+        Matrix::scaling(x, y, z) * self
+    }
+    pub fn rotate_x(self, radians: f32) -> Self {
+        Matrix::rotation_x(radians) * self
+    }
+    pub fn rotate_y(self, radians: f32) -> Self {
+        Matrix::rotation_y(radians) * self
+    }
+    pub fn rotate_z(self, radians: f32) -> Self {
+        Matrix::rotation_z(radians) * self
+    }
+    pub fn shear(self, x_y: f32, x_z: f32, y_x: f32, y_z: f32, z_x: f32, z_y: f32) -> Self {
+        Matrix::shearing(x_y, x_z, y_x, y_z, z_x, z_y) * self
+    }
+    /// Points pick up translation, vectors do not. Projective matrices are divided back
+    /// down to a w of 1, see `Tuple4::to_point`.
+    pub fn multiply_by_point(&self, point: &Point) -> Point {
+        self.multiply_by_tuple4(&Tuple4::from_point(point))
+            .to_point()
+    }
+    pub fn multiply_by_vector(&self, vector: &Vector) -> Vector {
+        self.multiply_by_tuple4(&Tuple4::from_vector(vector))
+            .to_vector()
+    }
+    pub fn multiply_by_tuple4(&self, tuple: &Tuple4) -> Tuple4 {
+        let tuple_row = [tuple.x, tuple.y, tuple.z, tuple.w];
 
-        (
-            new_vector,
-            Self::multiply_matrix_row(&self.points[3], &vec![1.0; 4]),
+        Tuple4::new(
+            Self::multiply_matrix_row(&self.points[0], &tuple_row),
+            Self::multiply_matrix_row(&self.points[1], &tuple_row),
+            Self::multiply_matrix_row(&self.points[2], &tuple_row),
+            Self::multiply_matrix_row(&self.points[3], &tuple_row),
         )
     }
     pub fn multiply_by_tuple(&self, tuple: (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
-        if self.points.len() != 4 {
-            panic!("Do not multiply a non 4x4 matrix by a tuple")
-        }
-        let mut new_tuple = (0.0, 0.0, 0.0, 0.0);
-
-        let tuple_row = vec![tuple.0, tuple.1, tuple.2, tuple.3];
-        new_tuple.0 = Self::multiply_matrix_row(&self.points[0], &tuple_row);
-        new_tuple.1 = Self::multiply_matrix_row(&self.points[1], &tuple_row);
-        new_tuple.2 = Self::multiply_matrix_row(&self.points[2], &tuple_row);
-        new_tuple.3 = Self::multiply_matrix_row(&self.points[3], &tuple_row);
-
-        new_tuple
-    }
-    pub fn equals(&self, other: &Matrix) -> bool {
-        if self.points.len() != other.points.len() {
-            return false;
-        };
-        if self.points[0].len() != other.points[0].len() {
-            return false;
-        };
-
-        for row in self.points.iter().enumerate() {
-            let (x, _) = row;
-            for point in row.1.iter().enumerate() {
-                let (y, _) = point;
-                if !crate::compare_float(other.points[x][y], self.points[x][y]) {
-                    return false;
+        let new_tuple = self.multiply_by_tuple4(&Tuple4::new(tuple.0, tuple.1, tuple.2, tuple.3));
+        (new_tuple.x, new_tuple.y, new_tuple.z, new_tuple.w)
+    }
+}
+
+// Submatrices shrink the size by one, which const generics can't express yet,
+// so minors and cofactors are stamped out for each size that needs them.
+macro_rules! impl_cofactors {
+    ($size:literal, $smaller:literal) => {
+        impl Matrix<$size> {
+            fn submatrix(
+                matrix: &Matrix<$size>,
+                row_to_delete: usize,
+                column_to_delete: usize,
+            ) -> Result<Matrix<$smaller>, &'static str> {
+                if row_to_delete >= $size || column_to_delete >= $size {
+                    return Err("Cannot delete a row that doesn't exist");
+                }
+
+                let mut new_matrix = Matrix::new();
+                let rows = matrix.points.iter().enumerate();
+                let kept_rows = rows.filter(|(x, _)| *x != row_to_delete);
+
+                for (new_row, (_, row)) in new_matrix.points.iter_mut().zip(kept_rows) {
+                    let columns = row.iter().enumerate();
+                    let kept_columns = columns.filter(|(y, _)| *y != column_to_delete);
+
+                    for (new_point, (_, point)) in new_row.iter_mut().zip(kept_columns) {
+                        *new_point = *point;
+                    }
+                }
+                Ok(new_matrix)
+            }
+            fn minor(
+                matrix: &Matrix<$size>,
+                row: usize,
+                column: usize,
+            ) -> Result<f32, &'static str> {
+                let submatrix = Self::submatrix(matrix, row, column)?;
+                Ok(submatrix.determinant())
+            }
+            fn cofactor(
+                matrix: &Matrix<$size>,
+                row: usize,
+                column: usize,
+            ) -> Result<f32, &'static str> {
+                let is_even = (row + column) % 2 == 0;
+                let minor = Self::minor(matrix, row, column)?;
+
+                if is_even {
+                    Ok(minor)
+                } else {
+                    Ok(-minor)
                 }
             }
         }
-        true
+    };
+}
+
+impl_cofactors!(3, 2);
+impl_cofactors!(4, 3);
+
+/// A transformation matrix stored alongside its inverse. Objects are intersected in their own
+/// space, so the inverse is needed for every ray and is only worth computing once.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    matrix: Matrix4,
+    inverse: Matrix4,
+}
+
+impl Transform {
+    pub fn new(matrix: Matrix4) -> Result<Self, MatrixError> {
+        Ok(Self {
+            matrix,
+            inverse: matrix.inverse()?,
+        })
     }
-    
-    pub fn is_invertible(&self) -> bool {
-        let determinant = self.get_determinant();
-        !crate::compare_float(determinant,0.0)
+    pub fn from_identity() -> Self {
+        Self {
+            matrix: Matrix::from_identity(),
+            inverse: Matrix::from_identity(),
+        }
+    }
+    pub fn matrix(&self) -> &Matrix4 {
+        &self.matrix
+    }
+    pub fn inverse(&self) -> &Matrix4 {
+        &self.inverse
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::from_identity()
+    }
+}
+
+impl TryFrom<Matrix4> for Transform {
+    type Error = MatrixError;
+
+    fn try_from(matrix: Matrix4) -> Result<Self, Self::Error> {
+        Self::new(matrix)
     }
 }
 
 #[cfg(test)]
 mod tests_matrix {
+    use crate::compare_float;
+    use crate::matrix::{Matrix, Matrix2, Matrix3, Matrix4, MatrixError, Transform};
+    use crate::points::{Point, Tuple4, Vector};
     use log::debug;
-    use crate::matrix::Matrix;
-    use crate::points::{Point, Vector};
+    use std::f32::consts::PI;
 
     #[test]
     fn test_4_4() {
-        let mut matrix4 = Matrix::new(4, 4);
+        let mut matrix4 = Matrix4::new();
         matrix4.points[0][0] = 11.2;
         matrix4.points[0][1] = 1.2;
         matrix4.points[3][3] = 22.2;
@@ -300,78 +432,78 @@ mod tests_matrix {
     }
     #[test]
     fn test_2_2() {
-        let matrix2 = Matrix::new(2, 2);
+        let matrix2 = Matrix2::new();
         assert_eq!(matrix2.points[0][0], 0.0);
     }
     #[test]
     fn test_matrix_comparison() {
-        let mut matrix1 = Matrix::new(4, 4);
-        matrix1.points[0] = vec![1.0, 2.0, 3.0, 4.0];
-        matrix1.points[1] = vec![5.0, 6.0, 7.0, 8.0];
-        matrix1.points[2] = vec![9.0, 8.0, 7.0, 6.0];
-        matrix1.points[3] = vec![5.0, 4.0, 3.0, 2.0];
-
-        let mut matrix2 = Matrix::new(4, 4);
-        matrix2.points[0] = vec![1.0, 2.0, 3.0, 4.0];
-        matrix2.points[1] = vec![5.0, 6.0, 7.0, 8.0];
-        matrix2.points[2] = vec![9.0, 8.0, 7.0, 6.0];
-        matrix2.points[3] = vec![5.0, 4.0, 3.0, 2.0];
-
-        let mut matrix3 = Matrix::new(4, 4);
-        matrix3.points[0] = vec![2.0, 2.0, 3.0, 4.0];
-        matrix3.points[1] = vec![3.0, 6.0, 7.0, 8.0];
-        matrix3.points[2] = vec![4.0, 8.0, 7.0, 6.0];
-        matrix3.points[3] = vec![6.0, 4.0, 3.0, 2.0];
+        let mut matrix1 = Matrix4::new();
+        matrix1.points[0] = [1.0, 2.0, 3.0, 4.0];
+        matrix1.points[1] = [5.0, 6.0, 7.0, 8.0];
+        matrix1.points[2] = [9.0, 8.0, 7.0, 6.0];
+        matrix1.points[3] = [5.0, 4.0, 3.0, 2.0];
+
+        let mut matrix2 = Matrix4::new();
+        matrix2.points[0] = [1.0, 2.0, 3.0, 4.0];
+        matrix2.points[1] = [5.0, 6.0, 7.0, 8.0];
+        matrix2.points[2] = [9.0, 8.0, 7.0, 6.0];
+        matrix2.points[3] = [5.0, 4.0, 3.0, 2.0];
+
+        let mut matrix3 = Matrix4::new();
+        matrix3.points[0] = [2.0, 2.0, 3.0, 4.0];
+        matrix3.points[1] = [3.0, 6.0, 7.0, 8.0];
+        matrix3.points[2] = [4.0, 8.0, 7.0, 6.0];
+        matrix3.points[3] = [6.0, 4.0, 3.0, 2.0];
 
         assert!(matrix1.equals(&matrix2));
         assert!(!matrix1.equals(&matrix3));
         assert!(matrix1.equals(&matrix1));
-        assert!(!matrix1.equals(&Matrix::new(4, 4)));
+        assert!(!matrix1.equals(&Matrix4::new()));
     }
     #[test]
     fn test_matrix_multiplication() {
-        let mut matrix1 = Matrix::new(4, 4);
-        matrix1.points[0] = vec![1.0, 2.0, 3.0, 4.0];
-        matrix1.points[1] = vec![5.0, 6.0, 7.0, 8.0];
-        matrix1.points[2] = vec![9.0, 8.0, 7.0, 6.0];
-        matrix1.points[3] = vec![5.0, 4.0, 3.0, 2.0];
-
-        let mut matrix2 = Matrix::new(4, 4);
-        matrix2.points[0] = vec![-2.0, 1.0, 2.0, 3.0];
-        matrix2.points[1] = vec![3.0, 2.0, 1.0, -1.0];
-        matrix2.points[2] = vec![4.0, 3.0, 6.0, 5.0];
-        matrix2.points[3] = vec![1.0, 2.0, 7.0, 8.0];
-
-        let mut correct_matrix = Matrix::new(4, 4);
-        correct_matrix.points[0] = vec![20.0, 22.0, 50.0, 48.0];
-        correct_matrix.points[1] = vec![44.0, 54.0, 114.0, 108.0];
-        correct_matrix.points[2] = vec![40.0, 58.0, 110.0, 102.0];
-        correct_matrix.points[3] = vec![16.0, 26.0, 46.0, 42.0];
+        let mut matrix1 = Matrix4::new();
+        matrix1.points[0] = [1.0, 2.0, 3.0, 4.0];
+        matrix1.points[1] = [5.0, 6.0, 7.0, 8.0];
+        matrix1.points[2] = [9.0, 8.0, 7.0, 6.0];
+        matrix1.points[3] = [5.0, 4.0, 3.0, 2.0];
+
+        let mut matrix2 = Matrix4::new();
+        matrix2.points[0] = [-2.0, 1.0, 2.0, 3.0];
+        matrix2.points[1] = [3.0, 2.0, 1.0, -1.0];
+        matrix2.points[2] = [4.0, 3.0, 6.0, 5.0];
+        matrix2.points[3] = [1.0, 2.0, 7.0, 8.0];
+
+        let mut correct_matrix = Matrix4::new();
+        correct_matrix.points[0] = [20.0, 22.0, 50.0, 48.0];
+        correct_matrix.points[1] = [44.0, 54.0, 114.0, 108.0];
+        correct_matrix.points[2] = [40.0, 58.0, 110.0, 102.0];
+        correct_matrix.points[3] = [16.0, 26.0, 46.0, 42.0];
         println!("{:?}", Matrix::multiply_matrices(&matrix1, &matrix2));
         println!("{:?}", correct_matrix);
         assert!(correct_matrix.equals(&Matrix::multiply_matrices(&matrix1, &matrix2)))
     }
     #[test]
     fn test_matrix_tuple_multiplication() {
-        let mut matrix = Matrix::new(4, 4);
-        matrix.points[0] = vec![1.0, 2.0, 3.0, 4.0];
-        matrix.points[1] = vec![2.0, 4.0, 4.0, 2.0];
-        matrix.points[2] = vec![8.0, 6.0, 4.0, 1.0];
-        matrix.points[3] = vec![0.0, 0.0, 0.0, 1.0];
+        let mut matrix = Matrix4::new();
+        matrix.points[0] = [1.0, 2.0, 3.0, 4.0];
+        matrix.points[1] = [2.0, 4.0, 4.0, 2.0];
+        matrix.points[2] = [8.0, 6.0, 4.0, 1.0];
+        matrix.points[3] = [0.0, 0.0, 0.0, 1.0];
 
         // Point x matrix
         let point = Point::new(1.0, 2.0, 3.0);
         let correct_answer = Point::new(18.0, 24.0, 33.0);
 
         let computed_answer = matrix.multiply_by_point(&point);
-        assert!(correct_answer.compare(&computed_answer.0));
+        assert!(correct_answer.compare(&computed_answer));
 
-        // Vector x Matrix
+        // Vector x Matrix, w is 0 so the last column is ignored
         let vector = Vector::new(1.0, 2.0, 3.0);
         let computed_answer = matrix.multiply_by_vector(&vector);
-        let correct_answer = Vector::new(18.0, 24.0, 33.0);
+        let correct_answer = Vector::new(14.0, 22.0, 32.0);
 
-        assert!(correct_answer.is_same(&computed_answer.0));
+        assert!(correct_answer.is_same(&computed_answer));
 
         // Tuple x Matrix
         let tuple = (1.0, 2.0, 3.0, 1.0);
@@ -382,65 +514,61 @@ mod tests_matrix {
     }
     #[test]
     fn test_multiplicative_identity() {
-        let mut matrix = Matrix::new(4, 4);
-        matrix.points[0] = vec![0.0, 1.0, 2.0, 3.0];
-        matrix.points[1] = vec![1.0, 2.0, 4.0, 8.0];
-        matrix.points[2] = vec![2.0, 4.0, 8.0, 16.0];
-        matrix.points[3] = vec![4.0, 8.0, 16.0, 32.0];
+        let mut matrix = Matrix4::new();
+        matrix.points[0] = [0.0, 1.0, 2.0, 3.0];
+        matrix.points[1] = [1.0, 2.0, 4.0, 8.0];
+        matrix.points[2] = [2.0, 4.0, 8.0, 16.0];
+        matrix.points[3] = [4.0, 8.0, 16.0, 32.0];
 
-        let matrix2 = matrix.multiply_by_matrix(&Matrix::from_identity());
+        let matrix2 = matrix.multiply_by_matrix(&Matrix4::from_identity());
 
         assert!(matrix.equals(&matrix2));
 
         let tuple = (1.0, 2.0, 3.0, 4.0);
 
-        let identity_matrix = Matrix::from_identity();
+        let identity_matrix = Matrix4::from_identity();
         let new_tuple = identity_matrix.multiply_by_tuple(tuple);
         assert_eq!(tuple, new_tuple);
     }
     #[test]
     fn test_matrix_transposition() {
-        let mut matrix = Matrix::new_4x4_from_rows(
-            vec![0.0, 9.0, 3.0, 0.0],
-            vec![9.0, 8.0, 0.0, 8.0],
-            vec![1.0, 8.0, 5.0, 3.0],
-            vec![0.0, 0.0, 5.0, 8.0],
-        );
-        let transposed_matrix = Matrix::new_4x4_from_rows(
-            vec![0.0, 9.0, 1.0, 0.0],
-            vec![9.0, 8.0, 8.0, 0.0],
-            vec![3.0, 0.0, 5.0, 5.0],
-            vec![0.0, 8.0, 3.0, 8.0],
-        );
+        let mut matrix = Matrix4::from_rows([
+            [0.0, 9.0, 3.0, 0.0],
+            [9.0, 8.0, 0.0, 8.0],
+            [1.0, 8.0, 5.0, 3.0],
+            [0.0, 0.0, 5.0, 8.0],
+        ]);
+        let transposed_matrix = Matrix4::from_rows([
+            [0.0, 9.0, 1.0, 0.0],
+            [9.0, 8.0, 8.0, 0.0],
+            [3.0, 0.0, 5.0, 5.0],
+            [0.0, 8.0, 3.0, 8.0],
+        ]);
 
         matrix.transpose();
         assert!(transposed_matrix.equals(&matrix));
 
         // Test IdentityMatrix transposition
-        let mut matrix = Matrix::from_identity();
+        let mut matrix = Matrix4::from_identity();
         matrix.transpose();
-        assert!(Matrix::from_identity().equals(&matrix));
+        assert!(Matrix4::from_identity().equals(&matrix));
     }
     #[test]
     fn test_invert_matrix() {}
     #[test]
     fn test_find_determinant_of_2x2() {
-        let mut matrix = Matrix::new(2, 2);
-        matrix.points[0] = vec![1.0, 5.0];
-        matrix.points[1] = vec![-3.0, 2.0];
+        let mut matrix = Matrix2::new();
+        matrix.points[0] = [1.0, 5.0];
+        matrix.points[1] = [-3.0, 2.0];
 
-        assert_eq!(matrix.get_determinant(), 17.0)
+        assert_eq!(matrix.determinant(), 17.0)
     }
     #[test]
     fn test_submatrix_3x3_to_2x2() {
-        let matrix = Matrix::new_3x3_from_rows(
-            vec![1.0, 5.0, 0.0],
-            vec![-3.0, 2.0, 7.0],
-            vec![0.0, 6.0, -3.0],
-        );
+        let matrix = Matrix3::from_rows([[1.0, 5.0, 0.0], [-3.0, 2.0, 7.0], [0.0, 6.0, -3.0]]);
 
-        let correct_submatrix = Matrix::new_2x2_from_rows(vec![-3.0, 2.0], vec![0.0, 6.0]);
-        let computed_submatrix = Matrix::submatrix(&matrix, 0, 2);
+        let correct_submatrix = Matrix2::from_rows([[-3.0, 2.0], [0.0, 6.0]]);
+        let computed_submatrix = Matrix3::submatrix(&matrix, 0, 2);
 
         println!("{:?} {:?}", correct_submatrix, computed_submatrix);
         assert!(computed_submatrix
@@ -449,57 +577,43 @@ mod tests_matrix {
     }
     #[test]
     fn test_submatrix_4x4_to_3x3() {
-        let matrix = Matrix::new_4x4_from_rows(
-            vec![-6.0, 1.0, 1.0, 6.0],
-            vec![-8.0, 5.0, 8.0, 6.0],
-            vec![-1.0, 0.0, 8.0, 2.0],
-            vec![-7.0, 1.0, -1.0, 1.0],
-        );
+        let matrix = Matrix4::from_rows([
+            [-6.0, 1.0, 1.0, 6.0],
+            [-8.0, 5.0, 8.0, 6.0],
+            [-1.0, 0.0, 8.0, 2.0],
+            [-7.0, 1.0, -1.0, 1.0],
+        ]);
 
-        let correct_submatrix = Matrix::new_3x3_from_rows(
-            vec![-6.0, 1.0, 6.0],
-            vec![-8.0, 8.0, 6.0],
-            vec![-7.0, -1.0, 1.0],
-        );
+        let correct_submatrix =
+            Matrix3::from_rows([[-6.0, 1.0, 6.0], [-8.0, 8.0, 6.0], [-7.0, -1.0, 1.0]]);
 
-        let computed_submatrix = Matrix::submatrix(&matrix, 2, 1);
+        let computed_submatrix = Matrix4::submatrix(&matrix, 2, 1);
         assert!(computed_submatrix
             .expect("Test data, should not panic")
             .equals(&correct_submatrix));
     }
     #[test]
     fn test_minor_3x3() {
-        let matrix = Matrix::new_3x3_from_rows(
-            vec![3.0, 5.0, 0.0],
-            vec![2.0, -1.0, -7.0],
-            vec![6.0, -1.0, 5.0],
-        );
+        let matrix = Matrix3::from_rows([[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]]);
 
-        let b_matrix = Matrix::submatrix(&matrix, 1, 0);
+        let b_matrix = Matrix3::submatrix(&matrix, 1, 0);
         let b_matrix = b_matrix.expect("Test data, should not panic");
 
         let correct_determinant = 25.0;
+        assert_eq!(correct_determinant, b_matrix.determinant());
         assert_eq!(
             correct_determinant,
-            b_matrix.get_determinant()
-        );
-        assert_eq!(
-            correct_determinant,
-            Matrix::minor(&matrix, 1, 0).expect("Test data is valid")
+            Matrix3::minor(&matrix, 1, 0).expect("Test data is valid")
         )
     }
     #[test]
     fn test_cofactors_3x3() {
-        let matrix = Matrix::new_3x3_from_rows(
-            vec![3.0, 5.0, 0.0],
-            vec![2.0, -1.0, -7.0],
-            vec![6.0, -1.0, 5.0],
-        );
+        let matrix = Matrix3::from_rows([[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]]);
 
-        let minor_1 = Matrix::minor(&matrix, 0, 0);
-        let cofactor_1 = Matrix::cofactor_3x3(&matrix, 0, 0);
-        let minor_2 = Matrix::minor(&matrix, 1, 0);
-        let cofactor_2 = Matrix::cofactor_3x3(&matrix, 1, 0);
+        let minor_1 = Matrix3::minor(&matrix, 0, 0);
+        let cofactor_1 = Matrix3::cofactor(&matrix, 0, 0);
+        let minor_2 = Matrix3::minor(&matrix, 1, 0);
+        let cofactor_2 = Matrix3::cofactor(&matrix, 1, 0);
 
         assert_eq!(minor_1.expect("Test data is valid"), -12.0);
         assert_eq!(minor_2.expect("Test data is valid"), 25.0);
@@ -509,158 +623,446 @@ mod tests_matrix {
 
     #[test]
     fn test_determinant_3x3() {
-        let matrix_1 = Matrix::new_3x3_from_rows(
-            vec![1.0, 2.0, 6.0],
-            vec![-5.0, 8.0, -4.0],
-            vec![2.0, 6.0, 4.0],
-        );
+        let matrix_1 = Matrix3::from_rows([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
 
+        assert_eq!(Matrix3::cofactor(&matrix_1, 0, 0).expect("Test Data"), 56.0);
+        assert_eq!(Matrix3::cofactor(&matrix_1, 0, 1).expect("Test Data"), 12.0);
         assert_eq!(
-            Matrix::cofactor_3x3(&matrix_1, 0, 0).expect("Test Data"),
-            56.0
-        );
-        assert_eq!(
-            Matrix::cofactor_3x3(&matrix_1, 0, 1).expect("Test Data"),
-            12.0
-        );
-        assert_eq!(
-            Matrix::cofactor_3x3(&matrix_1, 0, 2).expect("Test Data"),
+            Matrix3::cofactor(&matrix_1, 0, 2).expect("Test Data"),
             -46.0
         );
-        assert_eq!(matrix_1.get_determinant(), -196.0);
+        assert_eq!(matrix_1.determinant(), -196.0);
     }
     #[test]
     fn test_determinant_4x4() {
-        let matrix = Matrix::new_4x4_from_rows(
-            vec![-2.0, -8.0, 3.0, 5.0],
-            vec![-3.0, 1.0, 7.0, 3.0],
-            vec![1.0, 2.0, -9.0, 6.0],
-            vec![-6.0, 7.0, 7.0, -9.0],
-        );
+        let matrix = Matrix4::from_rows([
+            [-2.0, -8.0, 3.0, 5.0],
+            [-3.0, 1.0, 7.0, 3.0],
+            [1.0, 2.0, -9.0, 6.0],
+            [-6.0, 7.0, 7.0, -9.0],
+        ]);
 
-        assert_eq!(
-            Matrix::cofactor_4x4(&matrix, 0, 0).expect("Test Data"),
+        assert!(compare_float(
+            Matrix4::cofactor(&matrix, 0, 0).expect("Test Data"),
             690.0
-        );
-        assert_eq!(
-            Matrix::cofactor_4x4(&matrix, 0, 1).expect("Test Data"),
+        ));
+        assert!(compare_float(
+            Matrix4::cofactor(&matrix, 0, 1).expect("Test Data"),
             447.0
-        );
-        assert_eq!(
-            Matrix::cofactor_4x4(&matrix, 0, 2).expect("Test Data"),
+        ));
+        assert!(compare_float(
+            Matrix4::cofactor(&matrix, 0, 2).expect("Test Data"),
             210.0
-        );
-        assert_eq!(
-            Matrix::cofactor_4x4(&matrix, 0, 3).expect("Test Data"),
+        ));
+        assert!(compare_float(
+            Matrix4::cofactor(&matrix, 0, 3).expect("Test Data"),
             51.0
-        );
-        assert_eq!(matrix.get_determinant(), -4071.0);
+        ));
+        assert!(compare_float(matrix.determinant(), -4071.0));
     }
-    
+
     #[test]
-    fn test_identify_invertible(){
-        let matrix = Matrix::new_4x4_from_rows(
-            vec![6.0,4.0,4.0,4.0],
-            vec![5.0,5.0,7.0,6.0],
-            vec![4.0,-9.0,3.0,-7.0],
-            vec![9.0,1.0,7.0,-6.0]
-        );
-        
-        assert_eq!(matrix.get_determinant(), -2120.0);
+    fn test_identify_invertible() {
+        let matrix = Matrix4::from_rows([
+            [6.0, 4.0, 4.0, 4.0],
+            [5.0, 5.0, 7.0, 6.0],
+            [4.0, -9.0, 3.0, -7.0],
+            [9.0, 1.0, 7.0, -6.0],
+        ]);
+
+        assert!(compare_float(matrix.determinant(), -2120.0));
         assert!(matrix.is_invertible());
-        
-        
-        let non_invertible_matrix = Matrix::new_4x4_from_rows(
-            vec![-4.0,2.0,-2.0,-3.0],
-            vec![9.0,6.0,2.0,6.0],
-            vec![0.0,-5.0,1.0,-5.0],
-            vec![0.0,0.0,0.0,0.0]
-        );
+
+        let non_invertible_matrix = Matrix4::from_rows([
+            [-4.0, 2.0, -2.0, -3.0],
+            [9.0, 6.0, 2.0, 6.0],
+            [0.0, -5.0, 1.0, -5.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]);
         assert!(!non_invertible_matrix.is_invertible())
     }
-    
+
     #[test]
-    fn test_inverting(){
-        let matrix = Matrix::new_4x4_from_rows(
-            vec![-5.0,2.0,6.0,-8.0],
-            vec![1.0,-5.0,1.0,8.0],
-            vec![7.0,7.0,-6.0,-7.0],
-            vec![1.0,-3.0,7.0,4.0]
-        );
-        
-        let inverted = Matrix::inverse(&matrix).expect("matrix 1 is invertible");
-        
-        assert_eq!(Matrix::get_determinant(&matrix), 532.0);
-        assert_eq!(Matrix::cofactor_4x4(&matrix,2,3).expect("Valid input"),-160.0);
-        assert_eq!(Matrix::cofactor_4x4(&matrix,3,2).expect("Valid input"),105.0);
-        
-        
-        
-        let correct_inverted = Matrix::new_4x4_from_rows(
-            vec![0.21806,0.45113,0.24060,-0.04511],
-            vec![-0.80827,-1.45677,-0.44361,0.52068],
-            vec![-0.07895,-0.22368,-0.05263,0.19737],
-            vec![-0.52256,-0.81391,-0.30075,0.30639]
-        );
-        println!("inv: {:?} correct: {:?}",inverted,correct_inverted);
+    fn test_inverting() {
+        let matrix = Matrix4::from_rows([
+            [-5.0, 2.0, 6.0, -8.0],
+            [1.0, -5.0, 1.0, 8.0],
+            [7.0, 7.0, -6.0, -7.0],
+            [1.0, -3.0, 7.0, 4.0],
+        ]);
+
+        let inverted = Matrix4::inverse(&matrix).expect("matrix 1 is invertible");
+
+        assert!(compare_float(matrix.determinant(), 532.0));
+        assert!(compare_float(
+            Matrix4::cofactor(&matrix, 2, 3).expect("Valid input"),
+            -160.0
+        ));
+        assert!(compare_float(
+            Matrix4::cofactor(&matrix, 3, 2).expect("Valid input"),
+            105.0
+        ));
+
+        let correct_inverted = Matrix4::from_rows([
+            [0.21806, 0.45113, 0.24060, -0.04511],
+            [-0.80827, -1.45677, -0.44361, 0.52068],
+            [-0.07895, -0.22368, -0.05263, 0.19737],
+            [-0.52256, -0.81391, -0.30075, 0.30639],
+        ]);
+        println!("inv: {:?} correct: {:?}", inverted, correct_inverted);
         assert!(inverted.equals(&correct_inverted))
     }
-    
+
     #[test]
-    fn test_inverting_extra(){
+    fn test_inverting_extra() {
         // just really thoroughly testing the inverting because debugging it later will be a nightmare
-        let matrix = Matrix::new_4x4_from_rows(
-            vec![8.0,-5.0,9.0,2.0],
-            vec![7.0,5.0,6.0,1.0],
-            vec![-6.0,0.0,9.0,6.0],
-            vec![-3.0,0.0,-9.0,-4.0],
-        );
-        
-        let matrix_inverse = Matrix::new_4x4_from_rows(
-            vec![-0.15385,-0.15385,-0.28205,-0.53846],
-            vec![-0.07692,0.12308,0.02564,0.03077],
-            vec![0.35897,0.35897,0.43590,0.92308],
-            vec![-0.69231,-0.69231,-0.76923,-1.92308]
-        );
-        
-        assert!(Matrix::inverse(&matrix).expect("Test data").equals(&matrix_inverse));
-        
-        let matrix = Matrix::new_4x4_from_rows(
-            vec![9.0,3.0,0.0,9.0],
-            vec![-5.0,-2.0,-6.0,-3.0],
-            vec![-4.0,9.0,6.0,4.0],
-            vec![-7.0,6.0,6.0,2.0]
-        );
-        
-        let matrix_inverse = Matrix::new_4x4_from_rows(
-            vec![-0.04074,-0.07778,0.14444,-0.22222],
-            vec![-0.07778,0.03333,0.36667,-0.33333],
-            vec![-0.02901,-0.14630,-0.10926,0.12963],
-            vec![0.17778,0.06667,-0.26667,0.33333]
-        );
-        
-        assert!(Matrix::inverse(&matrix).expect("Test data is valid").equals(&matrix_inverse))
-    }
-    
-    
-    #[test]
-    fn test_multiply_by_inverse(){
-        let matrix = Matrix::new_4x4_from_rows(
-            vec![3.0,-9.0,7.0,3.0],
-            vec![3.0,-8.0,2.0,-9.0],
-            vec![-4.0,4.0,4.0,1.0],
-            vec![-6.0,5.0,-1.0,1.0]
-        );
-        
-        let matrix_b = Matrix::new_4x4_from_rows(
-            vec![8.0,2.0,2.0,2.0],
-            vec![3.0,-1.0,7.0,0.0],
-            vec![7.0,0.0,5.0,4.0],
-            vec![6.0,-2.0,0.0,5.0]
-        );
+        let matrix = Matrix4::from_rows([
+            [8.0, -5.0, 9.0, 2.0],
+            [7.0, 5.0, 6.0, 1.0],
+            [-6.0, 0.0, 9.0, 6.0],
+            [-3.0, 0.0, -9.0, -4.0],
+        ]);
+
+        let matrix_inverse = Matrix4::from_rows([
+            [-0.15385, -0.15385, -0.28205, -0.53846],
+            [-0.07692, 0.12308, 0.02564, 0.03077],
+            [0.35897, 0.35897, 0.43590, 0.92308],
+            [-0.69231, -0.69231, -0.76923, -1.92308],
+        ]);
+
+        assert!(Matrix4::inverse(&matrix)
+            .expect("Test data")
+            .equals(&matrix_inverse));
+
+        let matrix = Matrix4::from_rows([
+            [9.0, 3.0, 0.0, 9.0],
+            [-5.0, -2.0, -6.0, -3.0],
+            [-4.0, 9.0, 6.0, 4.0],
+            [-7.0, 6.0, 6.0, 2.0],
+        ]);
+
+        let matrix_inverse = Matrix4::from_rows([
+            [-0.04074, -0.07778, 0.14444, -0.22222],
+            [-0.07778, 0.03333, 0.36667, -0.33333],
+            [-0.02901, -0.14630, -0.10926, 0.12963],
+            [0.17778, 0.06667, -0.26667, 0.33333],
+        ]);
+
+        assert!(Matrix4::inverse(&matrix)
+            .expect("Test data is valid")
+            .equals(&matrix_inverse))
+    }
+
+    #[test]
+    fn test_multiply_by_inverse() {
+        let matrix = Matrix4::from_rows([
+            [3.0, -9.0, 7.0, 3.0],
+            [3.0, -8.0, 2.0, -9.0],
+            [-4.0, 4.0, 4.0, 1.0],
+            [-6.0, 5.0, -1.0, 1.0],
+        ]);
+
+        let matrix_b = Matrix4::from_rows([
+            [8.0, 2.0, 2.0, 2.0],
+            [3.0, -1.0, 7.0, 0.0],
+            [7.0, 0.0, 5.0, 4.0],
+            [6.0, -2.0, 0.0, 5.0],
+        ]);
         // Test that if you multiply A and B you can get A back by multiplying the result by the inverse of B
         let matrix_c = matrix.multiply_by_matrix(&matrix_b);
-        let inverted_b = Matrix::inverse(&matrix_b).expect("b is invertible");
+        let inverted_b = Matrix4::inverse(&matrix_b).expect("b is invertible");
         assert!(matrix_c.multiply_by_matrix(&inverted_b).equals(&matrix))
     }
+
+    #[test]
+    fn test_translation() {
+        let transform = Matrix4::translation(5.0, -3.0, 2.0);
+        let point = Point::new(-3.0, 4.0, 5.0);
+
+        let translated = transform.multiply_by_point(&point);
+        assert!(translated.compare(&Point::new(2.0, 1.0, 7.0)));
+
+        let inverse = Matrix4::inverse(&transform).expect("Translations are invertible");
+        let translated_back = inverse.multiply_by_point(&point);
+        assert!(translated_back.compare(&Point::new(-8.0, 7.0, 3.0)));
+    }
+
+    #[test]
+    fn test_scaling() {
+        let transform = Matrix4::scaling(2.0, 3.0, 4.0);
+
+        let point = transform.multiply_by_point(&Point::new(-4.0, 6.0, 8.0));
+        assert!(point.compare(&Point::new(-8.0, 18.0, 32.0)));
+
+        let vector = transform.multiply_by_vector(&Vector::new(-4.0, 6.0, 8.0));
+        assert!(vector.is_same(&Vector::new(-8.0, 18.0, 32.0)));
+
+        let inverse = Matrix4::inverse(&transform).expect("Scaling by non zero is invertible");
+        let vector = inverse.multiply_by_vector(&Vector::new(-4.0, 6.0, 8.0));
+        assert!(vector.is_same(&Vector::new(-2.0, 2.0, 2.0)));
+
+        // Reflection is scaling by a negative value
+        let reflection = Matrix4::scaling(-1.0, 1.0, 1.0);
+        let point = reflection.multiply_by_point(&Point::new(2.0, 3.0, 4.0));
+        assert!(point.compare(&Point::new(-2.0, 3.0, 4.0)));
+    }
+
+    #[test]
+    fn test_rotation_x() {
+        let point = Point::new(0.0, 1.0, 0.0);
+        let half_quarter = Matrix4::rotation_x(PI / 4.0);
+        let full_quarter = Matrix4::rotation_x(PI / 2.0);
+
+        let rotated = half_quarter.multiply_by_point(&point);
+        let half_sqrt = f32::sqrt(2.0) / 2.0;
+        assert!(rotated.compare(&Point::new(0.0, half_sqrt, half_sqrt)));
+        let rotated = full_quarter.multiply_by_point(&point);
+        assert!(rotated.compare(&Point::new(0.0, 0.0, 1.0)));
+
+        // The inverse rotates in the opposite direction
+        let inverse = Matrix4::inverse(&half_quarter).expect("Rotations are invertible");
+        let rotated = inverse.multiply_by_point(&point);
+        assert!(rotated.compare(&Point::new(0.0, half_sqrt, -half_sqrt)));
+    }
+
+    #[test]
+    fn test_rotation_y() {
+        let point = Point::new(0.0, 0.0, 1.0);
+        let half_sqrt = f32::sqrt(2.0) / 2.0;
+
+        let rotated = Matrix4::rotation_y(PI / 4.0).multiply_by_point(&point);
+        assert!(rotated.compare(&Point::new(half_sqrt, 0.0, half_sqrt)));
+        let rotated = Matrix4::rotation_y(PI / 2.0).multiply_by_point(&point);
+        assert!(rotated.compare(&Point::new(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_rotation_z() {
+        let point = Point::new(0.0, 1.0, 0.0);
+        let half_sqrt = f32::sqrt(2.0) / 2.0;
+
+        let rotated = Matrix4::rotation_z(PI / 4.0).multiply_by_point(&point);
+        assert!(rotated.compare(&Point::new(-half_sqrt, half_sqrt, 0.0)));
+        let rotated = Matrix4::rotation_z(PI / 2.0).multiply_by_point(&point);
+        assert!(rotated.compare(&Point::new(-1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_shearing() {
+        let point = Point::new(2.0, 3.0, 4.0);
+        let cases = [
+            (
+                Matrix4::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+                Point::new(5.0, 3.0, 4.0),
+            ),
+            (
+                Matrix4::shearing(0.0, 1.0, 0.0, 0.0, 0.0, 0.0),
+                Point::new(6.0, 3.0, 4.0),
+            ),
+            (
+                Matrix4::shearing(0.0, 0.0, 1.0, 0.0, 0.0, 0.0),
+                Point::new(2.0, 5.0, 4.0),
+            ),
+            (
+                Matrix4::shearing(0.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+                Point::new(2.0, 7.0, 4.0),
+            ),
+            (
+                Matrix4::shearing(0.0, 0.0, 0.0, 0.0, 1.0, 0.0),
+                Point::new(2.0, 3.0, 6.0),
+            ),
+            (
+                Matrix4::shearing(0.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+                Point::new(2.0, 3.0, 7.0),
+            ),
+        ];
+
+        for (transform, expected) in cases.iter() {
+            assert!(transform.multiply_by_point(&point).compare(expected));
+        }
+    }
+
+    #[test]
+    fn test_chained_transformations() {
+        let point = Point::new(1.0, 0.0, 1.0);
+        let rotation = Matrix4::rotation_x(PI / 2.0);
+        let scaling = Matrix4::scaling(5.0, 5.0, 5.0);
+        let translation = Matrix4::translation(10.0, 5.0, 7.0);
+
+        // Applied one at a time
+        let rotated = rotation.multiply_by_point(&point);
+        assert!(rotated.compare(&Point::new(1.0, -1.0, 0.0)));
+        let scaled = scaling.multiply_by_point(&rotated);
+        assert!(scaled.compare(&Point::new(5.0, -5.0, 0.0)));
+        let translated = translation.multiply_by_point(&scaled);
+        assert!(translated.compare(&Point::new(15.0, 0.0, 7.0)));
+
+        // Chained transforms are applied in reverse order
+        let combined = translation
+            .multiply_by_matrix(&scaling)
+            .multiply_by_matrix(&rotation);
+        assert!(combined.multiply_by_point(&point).compare(&translated));
+
+        // The fluent API reads in the order the transforms are applied
+        let fluent = Matrix4::from_identity()
+            .rotate_x(PI / 2.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0);
+        assert!(fluent.equals(&combined));
+        assert!(fluent.multiply_by_point(&point).compare(&translated));
+    }
+
+    #[test]
+    fn test_fluent_shear() {
+        let transform = Matrix4::from_identity().shear(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        let point = transform.multiply_by_point(&Point::new(2.0, 3.0, 4.0));
+        assert!(point.compare(&Point::new(5.0, 3.0, 4.0)));
+    }
+
+    #[test]
+    fn test_translation_does_not_affect_vectors() {
+        let transform = Matrix4::translation(5.0, -3.0, 2.0);
+        let vector = Vector::new(-3.0, 4.0, 5.0);
+
+        assert!(transform.multiply_by_vector(&vector).is_same(&vector));
+    }
+
+    #[test]
+    fn test_tuple4_w() {
+        let transform = Matrix4::translation(5.0, -3.0, 2.0);
+
+        let point = Tuple4::from_point(&Point::new(1.0, 2.0, 3.0));
+        let point = transform.multiply_by_tuple4(&point);
+        assert_eq!(point, Tuple4::new(6.0, -1.0, 5.0, 1.0));
+        assert!(point.is_point());
+
+        let vector = Tuple4::from_vector(&Vector::new(1.0, 2.0, 3.0));
+        let vector = transform.multiply_by_tuple4(&vector);
+        assert_eq!(vector, Tuple4::new(1.0, 2.0, 3.0, 0.0));
+        assert!(vector.is_vector());
+    }
+
+    #[test]
+    fn test_perspective_divide() {
+        // Projects onto the z = 1 plane, leaving w = z
+        let projection = Matrix4::from_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+        ]);
+
+        let projected = projection.multiply_by_point(&Point::new(2.0, 4.0, 2.0));
+        assert!(projected.compare(&Point::new(1.0, 2.0, 1.0)));
+    }
+
+    #[test]
+    fn test_matrix_operators() {
+        let matrix1 = Matrix4::from_rows([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 8.0, 7.0, 6.0],
+            [5.0, 4.0, 3.0, 2.0],
+        ]);
+        let matrix2 = Matrix4::from_rows([
+            [-2.0, 1.0, 2.0, 3.0],
+            [3.0, 2.0, 1.0, -1.0],
+            [4.0, 3.0, 6.0, 5.0],
+            [1.0, 2.0, 7.0, 8.0],
+        ]);
+
+        assert_eq!(
+            matrix1 * matrix2,
+            Matrix::multiply_matrices(&matrix1, &matrix2)
+        );
+        // Matrices are Copy, so both operands are still usable here
+        assert!((matrix1 * Matrix4::from_identity()).equals(&matrix1));
+
+        let tuple = Tuple4::new(1.0, 2.0, 3.0, 1.0);
+        assert_eq!(matrix1 * tuple, matrix1.multiply_by_tuple4(&tuple));
+    }
+
+    #[test]
+    fn test_smaller_matrices() {
+        let identity = Matrix3::from_identity();
+        let matrix = Matrix3::from_rows([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+
+        assert!((matrix * identity).equals(&matrix));
+        assert!(Matrix2::from_identity().equals(&Matrix2::from_rows([[1.0, 0.0], [0.0, 1.0]])));
+
+        let inverse = Matrix3::inverse(&matrix).expect("Determinant is -196");
+        assert!((matrix * inverse).equals(&identity));
+    }
+
+    #[test]
+    fn test_singular_matrix_errors() {
+        let singular = Matrix4::from_rows([
+            [-4.0, 2.0, -2.0, -3.0],
+            [9.0, 6.0, 2.0, 6.0],
+            [0.0, -5.0, 1.0, -5.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]);
+
+        assert_eq!(singular.determinant(), 0.0);
+        assert_eq!(singular.inverse(), Err(MatrixError::Singular));
+        assert_eq!(
+            singular.solve([1.0, 2.0, 3.0, 4.0]),
+            Err(MatrixError::Singular)
+        );
+        assert!(Transform::new(singular).is_err());
+    }
+
+    #[test]
+    fn test_pivoting() {
+        // The first pivot is zero, so elimination only works if rows get swapped
+        let matrix = Matrix2::from_rows([[0.0, 1.0], [2.0, 0.0]]);
+
+        assert_eq!(matrix.determinant(), -2.0);
+        let inverse = matrix.inverse().expect("Determinant is -2");
+        assert!(inverse.equals(&Matrix2::from_rows([[0.0, 0.5], [1.0, 0.0]])));
+
+        // Small but valid scales must not be mistaken for singular matrices
+        let tiny = Matrix4::scaling(0.001, 0.001, 0.001);
+        assert!(tiny.is_invertible());
+        assert!((tiny * tiny.inverse().expect("Invertible")).equals(&Matrix4::from_identity()));
+    }
+
+    #[test]
+    fn test_solve() {
+        let matrix = Matrix3::from_rows([[2.0, 1.0, -1.0], [-3.0, -1.0, 2.0], [-2.0, 1.0, 2.0]]);
+        let solution = matrix
+            .solve([8.0, -11.0, -3.0])
+            .expect("Matrix is invertible");
+
+        assert!(compare_float(solution[0], 2.0));
+        assert!(compare_float(solution[1], 3.0));
+        assert!(compare_float(solution[2], -1.0));
+    }
+
+    #[test]
+    fn test_transform_caches_inverse() {
+        let matrix = Matrix4::from_identity()
+            .scale(2.0, 2.0, 2.0)
+            .translate(1.0, 2.0, 3.0);
+        let transform = Transform::new(matrix).expect("Scaling and translation are invertible");
+
+        assert_eq!(*transform.matrix(), matrix);
+        assert!(transform
+            .inverse()
+            .equals(&matrix.inverse().expect("Invertible")));
+
+        let identity = Transform::from_identity();
+        assert_eq!(identity.inverse(), identity.matrix());
+    }
+
+    #[test]
+    fn test_point_and_vector_operators() {
+        let transform = Matrix4::translation(5.0, -3.0, 2.0);
+
+        let point = transform * Point::new(-3.0, 4.0, 5.0);
+        assert!(point.compare(&Point::new(2.0, 1.0, 7.0)));
+
+        let vector = transform * Vector::new(-3.0, 4.0, 5.0);
+        assert!(vector.is_same(&Vector::new(-3.0, 4.0, 5.0)));
+    }
 }
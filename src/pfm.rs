@@ -0,0 +1,202 @@
+//! Portable Float Map images, which store every channel as a raw 32 bit float
+use crate::prelude::*;
+use std::fmt;
+use std::io::{self, Write};
+
+impl Canvas {
+    /// Streams the canvas out as a little endian color PFM. Channels are written exactly as
+    /// they are, with rows running from the bottom of the image up as the format requires.
+    pub fn write_pfm<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        // A negative scale marks the samples as little endian
+        write!(writer, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+        for pixel in self.pixels.iter().rev().flatten() {
+            for channel in [pixel.red, pixel.green, pixel.blue] {
+                writer.write_all(&channel.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_pfm(&self) -> Vec<u8> {
+        let mut pfm = Vec::new();
+        self.write_pfm(&mut pfm)
+            .expect("Writing to a Vec can't fail");
+        pfm
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PfmError {
+    /// The magic number wasn't PF or Pf
+    UnsupportedFormat(String),
+    /// A dimension or scale that isn't a valid number
+    InvalidNumber(String),
+    /// The data stopped before every pixel had been read
+    UnexpectedEnd,
+}
+
+impl fmt::Display for PfmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PfmError::UnsupportedFormat(magic) => write!(f, "Unsupported PFM format {:?}", magic),
+            PfmError::InvalidNumber(token) => write!(f, "{:?} is not a valid number", token),
+            PfmError::UnexpectedEnd => write!(f, "PFM data ended early"),
+        }
+    }
+}
+
+impl std::error::Error for PfmError {}
+
+impl Canvas {
+    /// Reads a color (PF) or grayscale (Pf) PFM in either byte order. The magnitude of the
+    /// scale isn't applied, so the colors are exactly what the file stores.
+    pub fn from_pfm(data: &[u8]) -> Result<Canvas, PfmError> {
+        let mut reader = PpmReader { data, position: 0 };
+
+        let magic = reader.next_token().ok_or(PfmError::UnexpectedEnd)?;
+        let channels = match magic {
+            b"PF" => 3,
+            b"Pf" => 1,
+            _ => {
+                let magic = String::from_utf8_lossy(magic).into_owned();
+                return Err(PfmError::UnsupportedFormat(magic));
+            }
+        };
+        let width: i32 = next_number(&mut reader)?;
+        let height: i32 = next_number(&mut reader)?;
+        let scale: f32 = next_number(&mut reader)?;
+        if width < 0 || height < 0 {
+            return Err(PfmError::InvalidNumber(format!("{} {}", width, height)));
+        }
+        if scale == 0.0 || !scale.is_finite() {
+            return Err(PfmError::InvalidNumber(scale.to_string()));
+        }
+
+        // Like binary PPMs the samples start after a single whitespace character, and
+        // they're checked to all be there before the canvas is allocated
+        let start = reader.position + 1;
+        let end = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(channels * 4))
+            .and_then(|length| length.checked_add(start))
+            .ok_or(PfmError::UnexpectedEnd)?;
+        let bytes = data.get(start..end).ok_or(PfmError::UnexpectedEnd)?;
+
+        let samples: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|sample| {
+                let sample = [sample[0], sample[1], sample[2], sample[3]];
+                if scale < 0.0 {
+                    f32::from_le_bytes(sample)
+                } else {
+                    f32::from_be_bytes(sample)
+                }
+            })
+            .collect();
+
+        let mut canvas = Canvas::new(width, height);
+        for (index, pixel) in samples.chunks_exact(channels).enumerate() {
+            let x = (index % width as usize) as i32;
+            let y = height - 1 - (index / width as usize) as i32;
+            let color = match pixel {
+                [red, green, blue] => Color::new(*red, *green, *blue),
+                _ => Color::new(pixel[0], pixel[0], pixel[0]),
+            };
+            canvas.write_pixel(x, y, color);
+        }
+        Ok(canvas)
+    }
+}
+
+fn next_number<T: std::str::FromStr>(reader: &mut PpmReader<'_>) -> Result<T, PfmError> {
+    let token = reader.next_token().ok_or(PfmError::UnexpectedEnd)?;
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|token| token.parse().ok())
+        .ok_or_else(|| PfmError::InvalidNumber(String::from_utf8_lossy(token).into_owned()))
+}
+
+#[cfg(test)]
+mod pfm_tests {
+    use crate::pfm::*;
+
+    #[test]
+    fn test_writing_pfm() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_pixel(0, 0, Color::new(1.0, 2.0, 3.0));
+        canvas.write_pixel(1, 1, Color::new(-0.5, 0.0, 1000.0));
+
+        let pfm = canvas.to_pfm();
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(&pfm[..header.len()], header);
+        assert_eq!(pfm.len(), header.len() + 4 * 3 * 4);
+
+        // The bottom row comes first
+        let samples = &pfm[header.len()..];
+        assert_eq!(
+            samples[12..24],
+            [-0.5_f32, 0.0, 1000.0].map(f32::to_le_bytes).concat()
+        );
+        assert_eq!(
+            samples[24..36],
+            [1.0_f32, 2.0, 3.0].map(f32::to_le_bytes).concat()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut canvas = Canvas::new(5, 3);
+        for y in 0..3 {
+            for x in 0..5 {
+                let color = Color::new(x as f32 * 123.456, -(y as f32) / 7.0, 1e-7);
+                canvas.write_pixel(x, y, color);
+            }
+        }
+
+        let read = Canvas::from_pfm(&canvas.to_pfm()).expect("Valid PFM");
+        assert_eq!(read.width, 5);
+        assert_eq!(read.height, 3);
+        for y in 0..3 {
+            for x in 0..5 {
+                assert_eq!(read.pixel_at(x, y), canvas.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reading_big_endian_grayscale() {
+        let mut pfm = b"Pf\n1 2\n1.0\n".to_vec();
+        pfm.extend(0.25_f32.to_be_bytes());
+        pfm.extend(4.0_f32.to_be_bytes());
+
+        let canvas = Canvas::from_pfm(&pfm).expect("Valid PFM");
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(4.0, 4.0, 4.0));
+        assert_eq!(canvas.pixel_at(0, 1), Color::new(0.25, 0.25, 0.25));
+    }
+
+    #[test]
+    fn test_reading_malformed_pfm() {
+        let pfm = Canvas::new(2, 2).to_pfm();
+
+        assert_eq!(
+            Canvas::from_pfm(b"P6\n1 1\n255\n").err(),
+            Some(PfmError::UnsupportedFormat(String::from("P6")))
+        );
+        assert_eq!(
+            Canvas::from_pfm(b"PF\n1 x\n-1.0\n").err(),
+            Some(PfmError::InvalidNumber(String::from("x")))
+        );
+        assert_eq!(
+            Canvas::from_pfm(b"PF\n1 1\n0\n").err(),
+            Some(PfmError::InvalidNumber(String::from("0")))
+        );
+        assert_eq!(
+            Canvas::from_pfm(b"PF\n1 1\n").err(),
+            Some(PfmError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Canvas::from_pfm(&pfm[..pfm.len() - 1]).err(),
+            Some(PfmError::UnexpectedEnd)
+        );
+    }
+}
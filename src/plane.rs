@@ -0,0 +1,153 @@
+use crate::prelude::*;
+
+/// The xz plane through the origin, going on forever in both directions
+pub struct Plane {
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Plane {
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Plane {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    fn material(&self) -> &Material {
+        &self.material
+    }
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    /// A ray parallel to the plane never hits it, and one lying in the plane is treated the
+    /// same since the plane has no thickness to see
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        // The transform scales the direction, so it's compared by its angle to the plane
+        if (ray.direction.y / ray.direction.magnitude()).abs() < EPSILON {
+            return Intersections::new(vec![]);
+        }
+
+        let t = -ray.origin.position.y / ray.direction.y;
+        Intersections::new(vec![Intersection::new(t, self)])
+    }
+
+    /// The plane is flat, so the normal is the same everywhere
+    fn local_normal_at(&self, _point: Point) -> Vector {
+        Vector::new(0.0, 1.0, 0.0)
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(f32::NEG_INFINITY, 0.0, f32::NEG_INFINITY),
+            Point::new(f32::INFINITY, 0.0, f32::INFINITY),
+        )
+    }
+}
+
+#[cfg(test)]
+mod plane_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_normal_is_constant() {
+        let plane = Plane::new();
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        assert!(plane.local_normal_at(Point::zero()).is_same(&up));
+        assert!(plane
+            .local_normal_at(Point::new(10.0, 0.0, -10.0))
+            .is_same(&up));
+        assert!(plane
+            .local_normal_at(Point::new(-5.0, 0.0, 150.0))
+            .is_same(&up));
+    }
+
+    #[test]
+    fn test_ray_parallel_to_plane() {
+        let plane = Plane::new();
+
+        let ray = Ray::new(Point::new(0.0, 10.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(plane.local_intersect(&ray).is_empty());
+        // A coplanar ray doesn't see the plane either
+        let ray = Ray::new(Point::zero(), Vector::new(0.0, 0.0, 1.0));
+        assert!(plane.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn test_ray_from_above_and_below() {
+        let plane = Plane::new();
+
+        let ray = Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let xs = plane.local_intersect(&ray);
+        assert_eq!(xs.len(), 1);
+        assert!(compare_float(xs[0].t, 1.0));
+        assert!(std::ptr::addr_eq(xs[0].object, &plane));
+
+        let ray = Ray::new(Point::new(0.0, -1.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+        let xs = plane.local_intersect(&ray);
+        assert_eq!(xs.len(), 1);
+        assert!(compare_float(xs[0].t, 1.0));
+    }
+
+    #[test]
+    fn test_intersecting_transformed_plane() {
+        let mut plane = Plane::new();
+        plane.transform = Transform::new(
+            Matrix4::from_identity()
+                .rotate_x(std::f32::consts::PI / 2.0)
+                .translate(0.0, 0.0, 5.0),
+        )
+        .unwrap();
+        let ray = Ray::new(Point::zero(), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = plane.intersect(&ray);
+        assert_eq!(xs.len(), 1);
+        assert!(compare_float(xs[0].t, 5.0));
+        assert!(
+            plane
+                .normal_at(Point::new(0.0, 0.0, 5.0))
+                .dot_product(&Vector::new(0.0, 0.0, 1.0))
+                .abs()
+                > 1.0 - EPSILON
+        );
+    }
+
+    #[test]
+    fn test_plane_bounds() {
+        let bounds = Plane::new().bounds();
+
+        assert!(bounds.contains(&Point::new(1e30, 0.0, -1e30)));
+        assert!(!bounds.contains(&Point::new(0.0, 0.1, 0.0)));
+    }
+
+    #[test]
+    fn test_intersecting_scaled_plane() {
+        // Scaling along y shrinks the direction in object space, but not its angle to the plane
+        let mut plane = Plane::new();
+        plane.transform = Transform::new(Matrix4::scaling(1.0, 200.0, 1.0)).unwrap();
+        let ray = Ray::new(Point::new(0.0, 10.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+
+        let xs = plane.intersect(&ray);
+        assert_eq!(xs.len(), 1);
+        assert!(compare_float(xs[0].t, 10.0));
+
+        // A parallel ray still misses however the plane is scaled
+        plane.transform = Transform::new(Matrix4::scaling(1.0, 0.001, 1.0)).unwrap();
+        let ray = Ray::new(Point::new(0.0, 10.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert!(plane.intersect(&ray).is_empty());
+    }
+}
@@ -0,0 +1,855 @@
+use crate::prelude::*;
+use crate::zlib::{self, ZlibError};
+use std::fmt;
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+/// Large images are split over several IDAT chunks rather than one enormous one
+const MAX_IDAT_LENGTH: usize = 1 << 20;
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+/// The CRC-32 every PNG chunk ends with, calculated over the chunk type and data
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let crc = bytes.iter().fold(0xffff_ffff, |crc, byte| {
+        CRC_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    });
+    crc ^ 0xffff_ffff
+}
+
+/// The per row filters PNG can apply before compression, see
+/// <https://www.w3.org/TR/png/#9Filters>
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Filter {
+    None = 0,
+    Sub = 1,
+    Up = 2,
+    Average = 3,
+    Paeth = 4,
+}
+
+impl Filter {
+    fn from_byte(byte: u8) -> Option<Filter> {
+        Filter::ALL.get(usize::from(byte)).copied()
+    }
+
+    const ALL: [Filter; 5] = [
+        Filter::None,
+        Filter::Sub,
+        Filter::Up,
+        Filter::Average,
+        Filter::Paeth,
+    ];
+
+    /// Predicts each byte from its neighbours so only the difference has to be stored.
+    /// `left` is the byte one pixel back in this row, `up` and `up_left` the same in the row above.
+    fn predict(self, left: u8, up: u8, up_left: u8) -> u8 {
+        match self {
+            Filter::None => 0,
+            Filter::Sub => left,
+            Filter::Up => up,
+            Filter::Average => ((u16::from(left) + u16::from(up)) / 2) as u8,
+            Filter::Paeth => paeth_predictor(left, up, up_left),
+        }
+    }
+
+    fn apply(self, row: &[u8], previous_row: &[u8], bytes_per_pixel: usize) -> Vec<u8> {
+        let mut filtered = Vec::with_capacity(row.len() + 1);
+        filtered.push(self as u8);
+
+        for (x, byte) in row.iter().enumerate() {
+            let left = if x >= bytes_per_pixel {
+                row[x - bytes_per_pixel]
+            } else {
+                0
+            };
+            let up_left = if x >= bytes_per_pixel {
+                previous_row[x - bytes_per_pixel]
+            } else {
+                0
+            };
+            filtered.push(byte.wrapping_sub(self.predict(left, previous_row[x], up_left)));
+        }
+        filtered
+    }
+
+    /// Reverses `apply` in place
+    fn unapply(self, row: &mut [u8], previous_row: &[u8], bytes_per_pixel: usize) {
+        for x in 0..row.len() {
+            let left = if x >= bytes_per_pixel {
+                row[x - bytes_per_pixel]
+            } else {
+                0
+            };
+            let up_left = if x >= bytes_per_pixel {
+                previous_row[x - bytes_per_pixel]
+            } else {
+                0
+            };
+            row[x] = row[x].wrapping_add(self.predict(left, previous_row[x], up_left));
+        }
+    }
+}
+
+/// Picks whichever of left, up or up left is closest to `left + up - up_left`
+fn paeth_predictor(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let left_distance = (estimate - i16::from(left)).abs();
+    let up_distance = (estimate - i16::from(up)).abs();
+    let up_left_distance = (estimate - i16::from(up_left)).abs();
+
+    if left_distance <= up_distance && left_distance <= up_left_distance {
+        left
+    } else if up_distance <= up_left_distance {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Tries every filter on the row and keeps the one whose output has the smallest sum of
+/// absolute (signed) values, which tends to compress best.
+fn filter_row(row: &[u8], previous_row: &[u8], bytes_per_pixel: usize) -> Vec<u8> {
+    Filter::ALL
+        .iter()
+        .map(|filter| filter.apply(row, previous_row, bytes_per_pixel))
+        .min_by_key(|filtered| {
+            filtered[1..]
+                .iter()
+                .map(|byte| u32::from((*byte as i8).unsigned_abs()))
+                .sum::<u32>()
+        })
+        .expect("There is always at least one filter")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PngError {
+    InvalidSignature,
+    /// A chunk whose CRC doesn't match its contents
+    CorruptChunk(String),
+    MissingChunk(&'static str),
+    /// Valid PNG features this decoder doesn't handle, e.g. unknown critical chunks
+    Unsupported(String),
+    InvalidData(&'static str),
+    Compression(ZlibError),
+    UnexpectedEnd,
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PngError::InvalidSignature => write!(f, "Not a PNG file"),
+            PngError::CorruptChunk(chunk) => write!(f, "{} chunk failed its CRC check", chunk),
+            PngError::MissingChunk(chunk) => write!(f, "Missing {} chunk", chunk),
+            PngError::Unsupported(feature) => write!(f, "Unsupported PNG: {}", feature),
+            PngError::InvalidData(reason) => write!(f, "Invalid PNG: {}", reason),
+            PngError::Compression(error) => write!(f, "Invalid PNG image data: {}", error),
+            PngError::UnexpectedEnd => write!(f, "PNG data ended early"),
+        }
+    }
+}
+
+impl std::error::Error for PngError {}
+
+impl From<ZlibError> for PngError {
+    fn from(error: ZlibError) -> Self {
+        PngError::Compression(error)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ColorType {
+    Grayscale = 0,
+    Rgb = 2,
+    Indexed = 3,
+    GrayscaleAlpha = 4,
+    Rgba = 6,
+}
+
+impl ColorType {
+    fn channels(self) -> usize {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+
+    fn has_alpha(self) -> bool {
+        matches!(self, ColorType::GrayscaleAlpha | ColorType::Rgba)
+    }
+}
+
+/// The contents of the IHDR chunk
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: ColorType,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Header, PngError> {
+        let data: &[u8; 13] = data
+            .try_into()
+            .map_err(|_| PngError::InvalidData("IHDR must be 13 bytes long"))?;
+        let width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        let height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        let [bit_depth, color_type, compression, filter, interlace] =
+            [data[8], data[9], data[10], data[11], data[12]];
+
+        if width == 0 || height == 0 {
+            return Err(PngError::InvalidData("Image has no pixels"));
+        }
+        if width > i32::MAX as u32 || height > i32::MAX as u32 {
+            return Err(PngError::Unsupported(format!("{}x{} image", width, height)));
+        }
+        let color_type = match (color_type, bit_depth) {
+            (0, 1 | 2 | 4 | 8 | 16) => ColorType::Grayscale,
+            (2, 8 | 16) => ColorType::Rgb,
+            (3, 1 | 2 | 4 | 8) => ColorType::Indexed,
+            (4, 8 | 16) => ColorType::GrayscaleAlpha,
+            (6, 8 | 16) => ColorType::Rgba,
+            _ => {
+                return Err(PngError::Unsupported(format!(
+                    "color type {} with bit depth {}",
+                    color_type, bit_depth
+                )))
+            }
+        };
+        if compression != 0 || filter != 0 || interlace > 1 {
+            return Err(PngError::InvalidData(
+                "Unknown compression, filter or interlace method",
+            ));
+        }
+
+        Ok(Header {
+            width: width as usize,
+            height: height as usize,
+            bit_depth,
+            color_type,
+            interlaced: interlace == 1,
+        })
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.color_type.channels() * usize::from(self.bit_depth)
+    }
+
+    /// Filters work on whole bytes, so pixels smaller than a byte are compared a byte back
+    fn filter_bytes_per_pixel(&self) -> usize {
+        self.bits_per_pixel().div_ceil(8)
+    }
+
+    fn row_length(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    /// # Returns
+    /// The (x, y) starting position and spacing of each pass the image data is split into.
+    /// Interlaced images use the seven Adam7 passes, which fill in finer and finer grids.
+    fn passes(&self) -> Vec<(usize, usize, usize, usize)> {
+        if self.interlaced {
+            vec![
+                (0, 0, 8, 8),
+                (4, 0, 8, 8),
+                (0, 4, 4, 8),
+                (2, 0, 4, 4),
+                (0, 2, 2, 4),
+                (1, 0, 2, 2),
+                (0, 1, 1, 2),
+            ]
+        } else {
+            vec![(0, 0, 1, 1)]
+        }
+    }
+
+    fn max_sample(&self) -> f32 {
+        ((1_u32 << self.bit_depth) - 1) as f32
+    }
+
+    fn sample(&self, row: &[u8], index: usize) -> u16 {
+        match self.bit_depth {
+            16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+            8 => u16::from(row[index]),
+            depth => {
+                let bit = index * usize::from(depth);
+                let shift = 8 - usize::from(depth) - bit % 8;
+                u16::from((row[bit / 8] >> shift) & ((1 << depth) - 1))
+            }
+        }
+    }
+
+    /// Reads pixel `x` out of an unfiltered row, along with its opacity
+    fn pixel(
+        &self,
+        row: &[u8],
+        x: usize,
+        palette: &[Color],
+        transparency: &[u8],
+    ) -> Result<(Color, f32), PngError> {
+        let first = x * self.color_type.channels();
+        let sample = |offset| self.sample(row, first + offset);
+        let channel = |offset| f32::from(sample(offset)) / self.max_sample();
+        // For gray and RGB images tRNS holds the one color, as 16 bit samples, that's
+        // fully transparent
+        let key_alpha = |channels: usize| {
+            let is_key = transparency.len() == channels * 2
+                && (0..channels).all(|offset| {
+                    let key = [transparency[offset * 2], transparency[offset * 2 + 1]];
+                    u16::from_be_bytes(key) == sample(offset)
+                });
+            if is_key {
+                0.0
+            } else {
+                1.0
+            }
+        };
+
+        Ok(match self.color_type {
+            ColorType::Grayscale => {
+                let gray = channel(0);
+                (Color::new(gray, gray, gray), key_alpha(1))
+            }
+            ColorType::GrayscaleAlpha => {
+                let gray = channel(0);
+                (Color::new(gray, gray, gray), channel(1))
+            }
+            ColorType::Rgb => (Color::new(channel(0), channel(1), channel(2)), key_alpha(3)),
+            ColorType::Rgba => (Color::new(channel(0), channel(1), channel(2)), channel(3)),
+            ColorType::Indexed => {
+                let index = usize::from(sample(0));
+                let color = palette
+                    .get(index)
+                    .ok_or(PngError::InvalidData("Palette index out of range"))?;
+                // tRNS gives an alpha for each palette entry, any past its end are opaque
+                let alpha = transparency
+                    .get(index)
+                    .map_or(1.0, |alpha| f32::from(*alpha) / 255.0);
+                (*color, alpha)
+            }
+        })
+    }
+}
+
+/// # Returns
+/// The type and data of the chunk at `position`, and where the next chunk starts
+fn read_chunk(png: &[u8], position: usize) -> Result<([u8; 4], &[u8], usize), PngError> {
+    let header = png
+        .get(position..position + 8)
+        .ok_or(PngError::UnexpectedEnd)?;
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let chunk_type = [header[4], header[5], header[6], header[7]];
+
+    let data_start = position + 8;
+    let data = png
+        .get(data_start..data_start + length)
+        .ok_or(PngError::UnexpectedEnd)?;
+    let crc = png
+        .get(data_start + length..data_start + length + 4)
+        .ok_or(PngError::UnexpectedEnd)?;
+    if crc != crc32(&png[position + 4..data_start + length]).to_be_bytes() {
+        let name = String::from_utf8_lossy(&chunk_type).into_owned();
+        return Err(PngError::CorruptChunk(name));
+    }
+    Ok((chunk_type, data, data_start + length + 4))
+}
+
+fn write_chunk<W: Write>(writer: &mut W, chunk_type: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk is too large"))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(chunk_type)?;
+    writer.write_all(data)?;
+
+    let mut crc_data = chunk_type.to_vec();
+    crc_data.extend_from_slice(data);
+    writer.write_all(&crc32(&crc_data).to_be_bytes())
+}
+
+impl Canvas {
+    /// Writes the canvas as an 8 bit RGB PNG, or RGBA if it has an alpha channel. Colors are
+    /// clamped to 0..1 the same way as for PPM output.
+    pub fn write_png<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "PNG images must be at least 1x1",
+            ));
+        }
+        const BIT_DEPTH: u8 = 8;
+        let (color_type, bytes_per_pixel) = if self.has_alpha() {
+            (ColorType::Rgba, 4)
+        } else {
+            (ColorType::Rgb, 3)
+        };
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // Deflate compression, adaptive filtering and no interlacing
+        header.extend([BIT_DEPTH, color_type as u8, 0, 0, 0]);
+
+        let mut scanlines = Vec::new();
+        let mut previous_row = vec![0; self.width as usize * bytes_per_pixel];
+        for bytes in self.rows_8_bit() {
+            scanlines.extend(filter_row(&bytes, &previous_row, bytes_per_pixel));
+            previous_row = bytes;
+        }
+
+        writer.write_all(&SIGNATURE)?;
+        write_chunk(writer, b"IHDR", &header)?;
+        for data in zlib::compress(&scanlines).chunks(MAX_IDAT_LENGTH) {
+            write_chunk(writer, b"IDAT", data)?;
+        }
+        write_chunk(writer, b"IEND", &[])
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        self.write_png(&mut png)
+            .expect("Writing to a Vec can't fail");
+        png
+    }
+}
+
+impl Canvas {
+    /// Reads a PNG of any standard color type and bit depth, including interlaced images.
+    /// Colors are scaled to 0..1, and images with an alpha channel or a tRNS chunk give a
+    /// canvas with alpha.
+    pub fn from_png(data: &[u8]) -> Result<Canvas, PngError> {
+        let png = data
+            .strip_prefix(&SIGNATURE)
+            .ok_or(PngError::InvalidSignature)?;
+        let mut header = None;
+        let mut palette = Vec::new();
+        let mut transparency = Vec::new();
+        let mut image_data = Vec::new();
+        let mut position = 0;
+
+        loop {
+            let (chunk_type, data, next) = read_chunk(png, position)?;
+            match &chunk_type {
+                b"IHDR" => header = Some(Header::parse(data)?),
+                b"PLTE" => {
+                    if data.len() % 3 != 0 {
+                        return Err(PngError::InvalidData("PLTE length isn't a multiple of 3"));
+                    }
+                    palette = data
+                        .chunks_exact(3)
+                        .map(|rgb| {
+                            let [red, green, blue] = [rgb[0], rgb[1], rgb[2]].map(f32::from);
+                            Color::new(red / 255.0, green / 255.0, blue / 255.0)
+                        })
+                        .collect();
+                }
+                b"tRNS" => transparency = data.to_vec(),
+                b"IDAT" => image_data.extend_from_slice(data),
+                b"IEND" => break,
+                // Ancillary chunks (lowercase first letter) like gAMA or tEXt are safe to skip
+                _ if chunk_type[0].is_ascii_lowercase() => {}
+                _ => {
+                    let name = String::from_utf8_lossy(&chunk_type);
+                    return Err(PngError::Unsupported(format!("critical chunk {}", name)));
+                }
+            }
+            position = next;
+        }
+
+        let header = header.ok_or(PngError::MissingChunk("IHDR"))?;
+        if image_data.is_empty() {
+            return Err(PngError::MissingChunk("IDAT"));
+        }
+        if header.color_type == ColorType::Indexed && palette.is_empty() {
+            return Err(PngError::MissingChunk("PLTE"));
+        }
+        let scanlines = zlib::decompress(&image_data)?;
+
+        // Check there's enough data before allocating a canvas the size the header claims
+        let expected_length: usize = header
+            .passes()
+            .iter()
+            .map(|(x_start, y_start, x_step, y_step)| {
+                let width = header.width.saturating_sub(*x_start).div_ceil(*x_step);
+                let height = header.height.saturating_sub(*y_start).div_ceil(*y_step);
+                if width == 0 {
+                    0
+                } else {
+                    height * (1 + header.row_length(width))
+                }
+            })
+            .sum();
+        if scanlines.len() < expected_length {
+            return Err(PngError::UnexpectedEnd);
+        }
+
+        let (width, height) = (header.width as i32, header.height as i32);
+        let mut canvas = if header.color_type.has_alpha() || !transparency.is_empty() {
+            Canvas::with_alpha(width, height)
+        } else {
+            Canvas::new(width, height)
+        };
+        let mut rows = scanlines.as_slice();
+        for (x_start, y_start, x_step, y_step) in header.passes() {
+            let width = header.width.saturating_sub(x_start).div_ceil(x_step);
+            if width == 0 {
+                continue;
+            }
+            let row_length = header.row_length(width);
+            let mut previous_row = vec![0; row_length];
+
+            for y in (y_start..header.height).step_by(y_step) {
+                let filter = Filter::from_byte(rows[0])
+                    .ok_or(PngError::InvalidData("Unknown filter type"))?;
+                let mut row = rows[1..=row_length].to_vec();
+                rows = &rows[1 + row_length..];
+                filter.unapply(&mut row, &previous_row, header.filter_bytes_per_pixel());
+
+                for pass_x in 0..width {
+                    let x = x_start + pass_x * x_step;
+                    let (color, alpha) = header.pixel(&row, pass_x, &palette, &transparency)?;
+                    canvas.write_pixel_alpha(x as i32, y as i32, color, alpha);
+                }
+                previous_row = row;
+            }
+        }
+        Ok(canvas)
+    }
+}
+
+#[cfg(test)]
+mod png_tests {
+    use crate::compare_float;
+    use crate::png::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn test_paeth_predictor() {
+        assert_eq!(paeth_predictor(10, 20, 10), 20);
+        assert_eq!(paeth_predictor(20, 10, 10), 20);
+        assert_eq!(paeth_predictor(10, 10, 10), 10);
+        assert_eq!(paeth_predictor(100, 50, 200), 50);
+    }
+
+    #[test]
+    fn test_filters() {
+        let previous_row = [10, 20, 30, 40, 50, 60];
+        let row = [15, 25, 35, 45, 55, 65];
+
+        assert_eq!(
+            Filter::None.apply(&row, &previous_row, 3),
+            [0, 15, 25, 35, 45, 55, 65]
+        );
+        assert_eq!(
+            Filter::Sub.apply(&row, &previous_row, 3),
+            [1, 15, 25, 35, 30, 30, 30]
+        );
+        assert_eq!(
+            Filter::Up.apply(&row, &previous_row, 3),
+            [2, 5, 5, 5, 5, 5, 5]
+        );
+        assert_eq!(
+            Filter::Average.apply(&row, &previous_row, 3),
+            [3, 10, 15, 20, 18, 18, 18]
+        );
+        assert_eq!(
+            Filter::Paeth.apply(&row, &previous_row, 3),
+            [4, 5, 5, 5, 5, 5, 5]
+        );
+
+        // A flat row is cheapest to store as the difference from the row above
+        assert_eq!(filter_row(&row, &row, 3)[0], Filter::Up as u8);
+    }
+
+    #[test]
+    fn test_png_structure() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        let png = canvas.to_png();
+
+        assert_eq!(png[..8], SIGNATURE);
+        // IHDR is always first, with a fixed length of 13
+        assert_eq!(png[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(png[16..29], [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert_eq!(png[29..33], crc32(&png[12..29]).to_be_bytes());
+        assert_eq!(png[33 + 4..33 + 8], *b"IDAT");
+        assert_eq!(
+            png[png.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]
+        );
+    }
+
+    #[test]
+    fn test_empty_canvas_is_rejected() {
+        let canvas = Canvas::new(0, 10);
+        let mut png = Vec::new();
+
+        assert!(canvas.write_png(&mut png).is_err());
+    }
+
+    /// Builds a PNG around hand written scanlines (each starting with its filter type)
+    fn build_png(
+        size: (u32, u32),
+        bit_depth: u8,
+        color_type: u8,
+        interlace: u8,
+        scanlines: &[u8],
+        extra_chunks: &[(&[u8; 4], &[u8])],
+    ) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(size.0.to_be_bytes());
+        header.extend(size.1.to_be_bytes());
+        header.extend([bit_depth, color_type, 0, 0, interlace]);
+
+        let mut png = SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &header).expect("Writing to a Vec can't fail");
+        for (chunk_type, data) in extra_chunks {
+            write_chunk(&mut png, chunk_type, data).expect("Writing to a Vec can't fail");
+        }
+        write_chunk(&mut png, b"IDAT", &zlib::compress(scanlines))
+            .expect("Writing to a Vec can't fail");
+        write_chunk(&mut png, b"IEND", &[]).expect("Writing to a Vec can't fail");
+        png
+    }
+
+    fn gray(value: f32) -> Color {
+        Color::new(value, value, value)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut canvas = Canvas::new(37, 23);
+        for y in 0..23 {
+            for x in 0..37 {
+                let noise = ((x * 7919 + y * 104_729) % 97) as f32 / 97.0;
+                canvas.write_pixel(x, y, Color::new(x as f32 / 36.0, y as f32 / 22.0, noise));
+            }
+        }
+
+        let read = Canvas::from_png(&canvas.to_png()).expect("Valid PNG");
+        assert_eq!(read.width, 37);
+        assert_eq!(read.height, 23);
+        for y in 0..23 {
+            for x in 0..37 {
+                assert!(read.pixel_at(x, y).is_same(&canvas.pixel_at(x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unfiltering() {
+        let previous_row = [10, 20, 30, 40, 50, 60];
+        let row = [15, 25, 35, 45, 55, 65];
+
+        for filter in Filter::ALL {
+            let mut filtered = filter.apply(&row, &previous_row, 3)[1..].to_vec();
+            filter.unapply(&mut filtered, &previous_row, 3);
+            assert_eq!(filtered, row, "{:?} filter didn't round trip", filter);
+        }
+    }
+
+    #[test]
+    fn test_reading_16_bit_rgb() {
+        let scanlines = [0, 0xff, 0xff, 0x80, 0x00, 0, 0, 0, 0, 0, 0, 0x40, 0x00];
+        let png = build_png((2, 1), 16, 2, 0, &scanlines, &[]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+
+        assert!(canvas.pixel_at(0, 0).is_same(&Color::new(1.0, 0.5, 0.0)));
+        assert!(canvas.pixel_at(1, 0).is_same(&Color::new(0.0, 0.0, 0.25)));
+    }
+
+    #[test]
+    fn test_reading_low_bit_depth_grayscale() {
+        // Ten 1 bit pixels packed into two bytes, the last six bits are padding
+        let png = build_png((10, 1), 1, 0, 0, &[0, 0b1011_0000, 0b0100_0000], &[]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+        let expected = [1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+        for (x, value) in expected.iter().enumerate() {
+            assert!(canvas.pixel_at(x as i32, 0).is_same(&gray(*value)));
+        }
+
+        let png = build_png((3, 1), 4, 0, 0, &[0, 0x0f, 0x50], &[]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+        assert!(canvas.pixel_at(0, 0).is_same(&gray(0.0)));
+        assert!(canvas.pixel_at(1, 0).is_same(&gray(1.0)));
+        assert!(canvas.pixel_at(2, 0).is_same(&gray(1.0 / 3.0)));
+    }
+
+    #[test]
+    fn test_reading_palette() {
+        let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+        // 2 bits per index, rows of 3 pixels padded out to a byte
+        let scanlines = [0, 0b0001_1000, 0, 0b1110_0100];
+        let png = build_png((3, 2), 2, 3, 0, &scanlines, &[(b"PLTE", &palette)]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+
+        assert!(canvas.pixel_at(0, 0).is_same(&Color::new(1.0, 0.0, 0.0)));
+        assert!(canvas.pixel_at(1, 0).is_same(&Color::new(0.0, 1.0, 0.0)));
+        assert!(canvas.pixel_at(2, 0).is_same(&Color::new(0.0, 0.0, 1.0)));
+        assert!(canvas.pixel_at(0, 1).is_same(&Color::new(1.0, 1.0, 1.0)));
+
+        let png = build_png((1, 1), 8, 3, 0, &[0, 4], &[(b"PLTE", &palette)]);
+        assert_eq!(
+            Canvas::from_png(&png).err(),
+            Some(PngError::InvalidData("Palette index out of range"))
+        );
+        let png = build_png((1, 1), 8, 3, 0, &[0, 0], &[]);
+        assert_eq!(
+            Canvas::from_png(&png).err(),
+            Some(PngError::MissingChunk("PLTE"))
+        );
+    }
+
+    #[test]
+    fn test_reading_alpha_formats() {
+        // RGBA with a Sub filter on the second pixel
+        let scanlines = [1, 255, 0, 0, 128, 1, 255, 0, 127];
+        let png = build_png((2, 1), 8, 6, 0, &scanlines, &[]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+        assert!(canvas.has_alpha());
+        assert!(canvas.pixel_at(0, 0).is_same(&Color::new(1.0, 0.0, 0.0)));
+        assert!(compare_float(canvas.alpha_at(0, 0), 128.0 / 255.0));
+        assert!(canvas.pixel_at(1, 0).is_same(&Color::new(0.0, 1.0, 0.0)));
+        assert_eq!(canvas.alpha_at(1, 0), 1.0);
+
+        let scanlines = [0, 0x80, 0x00, 0x40, 0x00];
+        let png = build_png((1, 1), 16, 4, 0, &scanlines, &[]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+        assert!(canvas.pixel_at(0, 0).is_same(&gray(0.5)));
+        assert!(compare_float(canvas.alpha_at(0, 0), 0.25));
+
+        let png = build_png((1, 1), 8, 2, 0, &[0, 1, 2, 3], &[]);
+        assert!(!Canvas::from_png(&png).expect("Valid PNG").has_alpha());
+    }
+
+    #[test]
+    fn test_reading_transparency_chunk() {
+        let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        let chunks: [(&[u8; 4], &[u8]); 2] = [(b"PLTE", &palette), (b"tRNS", &[0, 51])];
+        let png = build_png((3, 1), 8, 3, 0, &[0, 0, 1, 2], &chunks);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+        assert_eq!(canvas.alpha_at(0, 0), 0.0);
+        assert!(compare_float(canvas.alpha_at(1, 0), 0.2));
+        assert_eq!(canvas.alpha_at(2, 0), 1.0);
+
+        // A gray key of 3 makes every pixel with that sample transparent
+        let png = build_png((2, 1), 4, 0, 0, &[0, 0x37], &[(b"tRNS", &[0, 3])]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+        assert_eq!(canvas.alpha_at(0, 0), 0.0);
+        assert_eq!(canvas.alpha_at(1, 0), 1.0);
+    }
+
+    #[test]
+    fn test_alpha_round_trip() {
+        let mut canvas = Canvas::with_alpha(3, 2);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.5, 0.0));
+        canvas.write_pixel_alpha(1, 0, Color::new(0.0, 0.0, 1.0), 0.4);
+
+        let png = canvas.to_png();
+        // Color type 6 is RGBA
+        assert_eq!(png[25], 6);
+        let read = Canvas::from_png(&png).expect("Valid PNG");
+        for y in 0..2 {
+            for x in 0..3 {
+                assert!(read.pixel_at(x, y).is_same(&canvas.pixel_at(x, y)));
+                assert!(compare_float(read.alpha_at(x, y), canvas.alpha_at(x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reading_interlaced() {
+        // A 3x3 image where each pixel is y * 3 + x, split into the Adam7 passes that aren't empty
+        let scanlines = [
+            0, 0, // pass 1: (0, 0)
+            0, 2, // pass 4: (2, 0)
+            0, 6, 8, // pass 5: (0, 2) and (2, 2)
+            0, 1, 0, 7, // pass 6: (1, 0) then (1, 2)
+            0, 3, 4, 5, // pass 7: the middle row
+        ];
+        let png = build_png((3, 3), 8, 0, 1, &scanlines, &[]);
+        let canvas = Canvas::from_png(&png).expect("Valid PNG");
+
+        for y in 0..3 {
+            for x in 0..3 {
+                let expected = gray((y * 3 + x) as f32 / 255.0);
+                assert!(canvas.pixel_at(x, y).is_same(&expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reading_skips_ancillary_chunks() {
+        let png = build_png((1, 1), 8, 0, 0, &[0, 255], &[(b"tEXt", b"Comment\0hi")]);
+        assert!(Canvas::from_png(&png).is_ok());
+
+        let png = build_png((1, 1), 8, 0, 0, &[0, 255], &[(b"ABCD", b"")]);
+        assert_eq!(
+            Canvas::from_png(&png).err(),
+            Some(PngError::Unsupported(String::from("critical chunk ABCD")))
+        );
+    }
+
+    #[test]
+    fn test_reading_malformed_png() {
+        let png = Canvas::new(2, 2).to_png();
+
+        assert_eq!(
+            Canvas::from_png(b"GIF89a").err(),
+            Some(PngError::InvalidSignature)
+        );
+        assert_eq!(
+            Canvas::from_png(&png[..png.len() - 6]).err(),
+            Some(PngError::UnexpectedEnd)
+        );
+
+        let mut corrupt = png.clone();
+        corrupt[20] ^= 1;
+        assert_eq!(
+            Canvas::from_png(&corrupt).err(),
+            Some(PngError::CorruptChunk(String::from("IHDR")))
+        );
+
+        let png = build_png((1, 1), 16, 3, 0, &[0, 0], &[]);
+        assert_eq!(
+            Canvas::from_png(&png).err(),
+            Some(PngError::Unsupported(String::from(
+                "color type 3 with bit depth 16"
+            )))
+        );
+        let png = build_png((2, 1), 8, 0, 0, &[5, 0, 0], &[]);
+        assert_eq!(
+            Canvas::from_png(&png).err(),
+            Some(PngError::InvalidData("Unknown filter type"))
+        );
+        let png = build_png((2, 2), 8, 0, 0, &[0, 0, 0], &[]);
+        assert_eq!(Canvas::from_png(&png).err(), Some(PngError::UnexpectedEnd));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::compare_float;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
-fn cross_product(vector1: &Vector, vector2: &Vector) -> Vector {
+pub fn cross_product(vector1: &Vector, vector2: &Vector) -> Vector {
     let x = vector1.y * vector2.z - vector1.z * vector2.y;
     let y = vector1.z * vector2.x - vector1.x * vector2.z;
     let z = vector1.x * vector2.y - vector1.y * vector2.x;
@@ -8,6 +9,7 @@ fn cross_product(vector1: &Vector, vector2: &Vector) -> Vector {
     Vector::new(x, y, z)
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Point {
     pub position: Vector,
 }
@@ -44,6 +46,45 @@ impl Point {
         self.position.is_same(&point.position)
     }
 }
+/// Homogeneous coordinates used when multiplying by a 4x4 matrix.
+/// Points carry a w of 1 so they pick up translation, vectors carry a w of 0 so they don't.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tuple4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+impl Tuple4 {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+    pub fn from_point(point: &Point) -> Self {
+        Self::new(point.position.x, point.position.y, point.position.z, 1.0)
+    }
+    pub fn from_vector(vector: &Vector) -> Self {
+        Self::new(vector.x, vector.y, vector.z, 0.0)
+    }
+    pub fn is_point(&self) -> bool {
+        compare_float(self.w, 1.0)
+    }
+    pub fn is_vector(&self) -> bool {
+        compare_float(self.w, 0.0)
+    }
+    /// Performs the perspective divide, so a projected tuple lands back on w = 1.
+    /// A w of 0 is a point at infinity and is returned undivided.
+    pub fn to_point(self) -> Point {
+        if self.w == 0.0 || self.w == 1.0 {
+            return Point::new(self.x, self.y, self.z);
+        }
+        Point::new(self.x / self.w, self.y / self.w, self.z / self.w)
+    }
+    pub fn to_vector(self) -> Vector {
+        Vector::new(self.x, self.y, self.z)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Vector {
     pub x: f32,
     pub y: f32,
@@ -59,9 +100,6 @@ impl Vector {
     pub fn copy(vector: &Vector) -> Self {
         Self::new(vector.x, vector.y, vector.z)
     }
-    pub fn clone(&self) -> Vector {
-        Vector::copy(self)
-    }
     pub fn invert(&mut self) {
         let mut inverted = Vector::zero();
         inverted.subtract(self);
@@ -72,14 +110,10 @@ impl Vector {
         self.y -= vector.y;
         self.z -= vector.z;
     }
-    pub fn apply(&mut self, vector: &Vector) -> Point {
+    pub fn apply(&mut self, vector: &Vector) {
         self.x += vector.x;
         self.y += vector.y;
         self.z += vector.z;
-
-        Point {
-            position: Vector::new(self.x, self.y, self.z),
-        }
     }
     pub fn overwrite(&mut self, vector: &Vector) {
         self.x = vector.x;
@@ -123,11 +157,127 @@ impl Vector {
 
         x + y + z
     }
+    /// # Returns
+    /// This vector bounced off a surface with the given normal, like light off a mirror
+    pub fn reflect(&self, normal: &Vector) -> Vector {
+        *self - *normal * 2.0 * self.dot_product(normal)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, rhs: Vector) -> Self::Output {
+        Vector::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, rhs: Vector) -> Self::Output {
+        Vector::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Vector::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Mul<Vector> for f32 {
+    type Output = Vector;
+
+    fn mul(self, rhs: Vector) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl Div<f32> for Vector {
+    type Output = Vector;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Vector::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Self::Output {
+        Vector::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign<f32> for Vector {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign<f32> for Vector {
+    fn div_assign(&mut self, rhs: f32) {
+        *self = *self / rhs;
+    }
+}
+
+// Points only support the operations that make sense for positions: moving a point by a
+// vector gives a point and the difference of two points is a vector. Adding two points
+// together is meaningless, so `Point + Point` deliberately doesn't compile.
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Vector) -> Self::Output {
+        Point::from_vector(&(self.position + rhs))
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Vector) -> Self::Output {
+        Point::from_vector(&(self.position - rhs))
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, rhs: Point) -> Self::Output {
+        self.position - rhs.position
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::points::{compare_float, cross_product, Point, Vector};
+    use crate::points::{compare_float, cross_product, Point, Tuple4, Vector};
 
     #[test]
     fn test_compare() {
@@ -153,7 +303,7 @@ mod tests {
         let c = Point::new(2.2, 2.1, 2.2);
 
         assert!(a.compare(&b));
-        assert_eq!(a.compare(&c), false);
+        assert!(!a.compare(&c));
     }
 
     #[test]
@@ -162,8 +312,8 @@ mod tests {
         let vector2 = Vector::zero();
         let vector3 = Vector::new(4.2, 2.4, 2.1);
 
-        assert_eq!(vector.is_same(&vector2), true);
-        assert_eq!(vector.is_same(&vector3), false);
+        assert!(vector.is_same(&vector2));
+        assert!(!vector.is_same(&vector3));
     }
 
     #[test]
@@ -238,7 +388,7 @@ mod tests {
     #[test]
     fn test_normalize() {
         let a = Vector::new(4.0, 0.0, 0.0);
-        let mut b = a.clone();
+        let mut b = a;
         b.normalize();
         let correct_vector = Vector::new(1.0, 0.0, 0.0);
 
@@ -274,4 +424,72 @@ mod tests {
         let b_a_cross_correct = Vector::new(1.0, -2.0, 1.0);
         assert!(b_a_cross.is_same(&b_a_cross_correct));
     }
+
+    #[test]
+    fn test_tuple4() {
+        let point = Tuple4::from_point(&Point::new(4.3, -4.2, 3.1));
+        assert_eq!(point, Tuple4::new(4.3, -4.2, 3.1, 1.0));
+        assert!(point.is_point());
+        assert!(!point.is_vector());
+
+        let vector = Tuple4::from_vector(&Vector::new(4.3, -4.2, 3.1));
+        assert_eq!(vector, Tuple4::new(4.3, -4.2, 3.1, 0.0));
+        assert!(vector.is_vector());
+        assert!(!vector.is_point());
+
+        let projected = Tuple4::new(2.0, 4.0, 6.0, 2.0);
+        assert!(projected.to_point().compare(&Point::new(1.0, 2.0, 3.0)));
+    }
+
+    #[test]
+    fn test_vector_operators() {
+        let a = Vector::new(3.0, -2.0, 5.0);
+        let b = Vector::new(-2.0, 3.0, 1.0);
+
+        assert!((a + b).is_same(&Vector::new(1.0, 1.0, 6.0)));
+        assert!((a - b).is_same(&Vector::new(5.0, -5.0, 4.0)));
+        assert!((-a).is_same(&Vector::new(-3.0, 2.0, -5.0)));
+        assert!((a * 2.0).is_same(&Vector::new(6.0, -4.0, 10.0)));
+        assert!((0.5 * a).is_same(&Vector::new(1.5, -1.0, 2.5)));
+        assert!((a / 2.0).is_same(&Vector::new(1.5, -1.0, 2.5)));
+
+        let mut c = a;
+        c += b;
+        assert!(c.is_same(&Vector::new(1.0, 1.0, 6.0)));
+        c -= b;
+        assert!(c.is_same(&a));
+        c *= 2.0;
+        assert!(c.is_same(&Vector::new(6.0, -4.0, 10.0)));
+        c /= 2.0;
+        assert!(c.is_same(&a));
+    }
+
+    #[test]
+    fn test_point_operators() {
+        let point = Point::new(3.0, 2.0, 1.0);
+        let other_point = Point::new(5.0, 6.0, 7.0);
+        let vector = Vector::new(5.0, 6.0, 7.0);
+
+        let difference: Vector = point - other_point;
+        assert!(difference.is_same(&Vector::new(-2.0, -4.0, -6.0)));
+        assert!((point + vector).compare(&Point::new(8.0, 8.0, 8.0)));
+        assert!((point - vector).compare(&Point::new(-2.0, -4.0, -6.0)));
+
+        let mut moved = point;
+        moved += vector;
+        assert!(moved.compare(&Point::new(8.0, 8.0, 8.0)));
+        moved -= vector;
+        assert!(moved.compare(&point));
+    }
+
+    #[test]
+    fn test_reflect() {
+        let vector = Vector::new(1.0, -1.0, 0.0);
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        assert!(vector.reflect(&normal).is_same(&Vector::new(1.0, 1.0, 0.0)));
+
+        let vector = Vector::new(0.0, -1.0, 0.0);
+        let normal = Vector::new(f32::sqrt(2.0) / 2.0, f32::sqrt(2.0) / 2.0, 0.0);
+        assert!(vector.reflect(&normal).is_same(&Vector::new(1.0, 0.0, 0.0)));
+    }
 }
@@ -0,0 +1,325 @@
+//! Polynomials in one variable and a root finder for them, for shapes whose surface is an
+//! equation of higher degree than the quadratic formula can solve. Everything is done in f64,
+//! since the coefficients of a quartic are products of four values and f32 loses too much.
+
+/// Coefficients this much smaller than the largest one are rounding noise, and dropped so
+/// they don't count as a leading term
+const NOISE: f64 = 1e-12;
+/// How narrow the interval a root is narrowed down to gets
+const TOLERANCE: f64 = 1e-10;
+/// Halving any interval more than this many times runs out of f64 precision anyway
+const MAX_BISECTIONS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    /// From the constant term up, never ending in a zero
+    coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    /// Takes the coefficients from the constant term up, so `[c, b, a]` is `ax² + bx + c`
+    pub fn new(coefficients: &[f64]) -> Self {
+        let mut coefficients = coefficients.to_vec();
+        while coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+        Self { coefficients }
+    }
+
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+    /// # Returns
+    /// The highest power of x, counting the zero polynomial as degree 0
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Evaluates the polynomial at `x` with Horner's method
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |sum, coefficient| sum * x + coefficient)
+    }
+
+    pub fn derivative(&self) -> Polynomial {
+        let coefficients: Vec<f64> = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(power, coefficient)| power as f64 * coefficient)
+            .collect();
+        Polynomial::new(&coefficients)
+    }
+
+    /// # Returns
+    /// The remainder of long division by `divisor`, which mustn't be zero
+    fn remainder(&self, divisor: &Polynomial) -> Polynomial {
+        let scale = self.largest_coefficient();
+        let leading = divisor.coefficients[divisor.degree()];
+        let mut remainder = self.coefficients.clone();
+
+        while remainder.len() >= divisor.coefficients.len() {
+            let quotient = remainder[remainder.len() - 1] / leading;
+            let shift = remainder.len() - divisor.coefficients.len();
+            for (power, coefficient) in divisor.coefficients.iter().enumerate() {
+                remainder[shift + power] -= quotient * coefficient;
+            }
+            // The leading term cancels out, leaving only rounding error behind
+            remainder.pop();
+            while remainder
+                .last()
+                .is_some_and(|coefficient| coefficient.abs() <= NOISE * scale)
+            {
+                remainder.pop();
+            }
+        }
+        Polynomial::new(&remainder)
+    }
+
+    fn largest_coefficient(&self) -> f64 {
+        self.coefficients
+            .iter()
+            .fold(0.0, |largest, coefficient| largest.max(coefficient.abs()))
+    }
+
+    /// Scales the polynomial so its largest coefficient is ±1, which keeps the values in a
+    /// Sturm sequence from overflowing without changing any of their signs
+    fn normalized(mut self) -> Polynomial {
+        let largest = self.largest_coefficient();
+        if largest > 0.0 {
+            for coefficient in &mut self.coefficients {
+                *coefficient /= largest;
+            }
+        }
+        self
+    }
+
+    /// # Returns
+    /// The Sturm sequence: the polynomial, its derivative, then the negated remainder of
+    /// dividing each pair in turn until it runs out
+    pub fn sturm_sequence(&self) -> Vec<Polynomial> {
+        let mut sequence = vec![self.clone().normalized(), self.derivative().normalized()];
+        loop {
+            let last = &sequence[sequence.len() - 1];
+            if last.is_zero() || last.degree() == 0 {
+                break;
+            }
+            let mut next = sequence[sequence.len() - 2].remainder(last);
+            for coefficient in &mut next.coefficients {
+                *coefficient = -*coefficient;
+            }
+            sequence.push(next.normalized());
+        }
+        sequence.retain(|polynomial| !polynomial.is_zero());
+        sequence
+    }
+
+    /// # Returns
+    /// Every distinct real root in `min..=max`, in ascending order. A repeated root is only
+    /// returned once.
+    pub fn roots_in(&self, min: f64, max: f64) -> Vec<f64> {
+        let mut roots = Vec::new();
+        if self.is_zero() || min > max {
+            return roots;
+        }
+
+        // The search uses the normalized polynomial from the sequence, so that it agrees with
+        // the sequence on exactly where the polynomial is zero
+        let sequence = self.sturm_sequence();
+        let normalized = &sequence[0];
+        // Sturm's theorem counts the roots in (min, max], so a root exactly at min is
+        // checked for on its own
+        if normalized.evaluate(min) == 0.0 {
+            roots.push(min);
+        }
+        let interval = (min, max);
+        let changes = (sign_changes(&sequence, min), sign_changes(&sequence, max));
+        normalized.isolate_roots(&sequence, interval, changes, 0, &mut roots);
+        roots
+    }
+
+    /// Halves the interval until each part holds a single root, then narrows in on it
+    fn isolate_roots(
+        &self,
+        sequence: &[Polynomial],
+        (min, max): (f64, f64),
+        (changes_at_min, changes_at_max): (usize, usize),
+        depth: usize,
+        roots: &mut Vec<f64>,
+    ) {
+        let count = changes_at_min.saturating_sub(changes_at_max);
+        if count == 0 {
+            return;
+        }
+        if count == 1 {
+            if let Some(root) = self.bisect(min, max) {
+                roots.push(root);
+                return;
+            }
+        }
+
+        // Either there's more than one root to separate, or a repeated root that the
+        // polynomial doesn't change sign across, so only the Sturm sequence can find it
+        let middle = min + (max - min) / 2.0;
+        if max - min < TOLERANCE || depth >= MAX_BISECTIONS {
+            roots.push(middle);
+            return;
+        }
+        let changes_at_middle = sign_changes(sequence, middle);
+        self.isolate_roots(
+            sequence,
+            (min, middle),
+            (changes_at_min, changes_at_middle),
+            depth + 1,
+            roots,
+        );
+        self.isolate_roots(
+            sequence,
+            (middle, max),
+            (changes_at_middle, changes_at_max),
+            depth + 1,
+            roots,
+        );
+    }
+
+    /// # Returns
+    /// The root in (min, max], if the polynomial changes sign across it
+    fn bisect(&self, mut min: f64, mut max: f64) -> Option<f64> {
+        let mut value_at_min = self.evaluate(min);
+        let value_at_max = self.evaluate(max);
+        if value_at_max == 0.0 {
+            return Some(max);
+        }
+        if value_at_min == 0.0 || (value_at_min < 0.0) == (value_at_max < 0.0) {
+            return None;
+        }
+
+        for _ in 0..MAX_BISECTIONS {
+            if max - min < TOLERANCE {
+                break;
+            }
+            let middle = min + (max - min) / 2.0;
+            let value = self.evaluate(middle);
+            if value == 0.0 {
+                return Some(middle);
+            }
+            if (value < 0.0) == (value_at_min < 0.0) {
+                min = middle;
+                value_at_min = value;
+            } else {
+                max = middle;
+            }
+        }
+        Some(min + (max - min) / 2.0)
+    }
+}
+
+/// # Returns
+/// How many times the signs of a Sturm sequence evaluated at `x` flip, skipping zeros
+fn sign_changes(sequence: &[Polynomial], x: f64) -> usize {
+    let signs: Vec<bool> = sequence
+        .iter()
+        .map(|polynomial| polynomial.evaluate(x))
+        .filter(|value| *value != 0.0)
+        .map(|value| value < 0.0)
+        .collect();
+    signs.windows(2).filter(|pair| pair[0] != pair[1]).count()
+}
+
+#[cfg(test)]
+mod polynomial_tests {
+    use crate::polynomial::*;
+
+    /// Multiplies out (x - root) for every root
+    fn from_roots(roots: &[f64]) -> Polynomial {
+        let mut coefficients = vec![1.0];
+        for root in roots {
+            let mut next = vec![0.0; coefficients.len() + 1];
+            for (power, coefficient) in coefficients.iter().enumerate() {
+                next[power + 1] += coefficient;
+                next[power] -= root * coefficient;
+            }
+            coefficients = next;
+        }
+        Polynomial::new(&coefficients)
+    }
+
+    fn assert_roots(found: &[f64], expected: &[f64]) {
+        assert_eq!(found.len(), expected.len(), "{:?} != {:?}", found, expected);
+        for (found, expected) in found.iter().zip(expected) {
+            assert!((found - expected).abs() < 1e-6, "{} != {}", found, expected);
+        }
+    }
+
+    #[test]
+    fn test_evaluating_polynomial() {
+        let polynomial = Polynomial::new(&[1.0, -3.0, 0.0, 2.0, 0.0, 0.0]);
+
+        assert_eq!(polynomial.degree(), 3);
+        assert_eq!(polynomial.coefficients(), [1.0, -3.0, 0.0, 2.0]);
+        assert_eq!(polynomial.evaluate(2.0), 11.0);
+        assert_eq!(polynomial.derivative(), Polynomial::new(&[-3.0, 0.0, 6.0]));
+        assert!(Polynomial::new(&[0.0]).is_zero());
+    }
+
+    #[test]
+    fn test_sturm_sequence() {
+        // x³ + 3x² - 1 has three roots, at about -2.88, -0.65 and 0.53
+        let polynomial = Polynomial::new(&[-1.0, 0.0, 3.0, 1.0]);
+        let sequence = polynomial.sturm_sequence();
+
+        assert_eq!(sequence.len(), 4);
+        assert_eq!(sign_changes(&sequence, -10.0), 3);
+        assert_eq!(sign_changes(&sequence, 0.0), 1);
+        assert_eq!(sign_changes(&sequence, 10.0), 0);
+    }
+
+    #[test]
+    fn test_quartic_roots() {
+        let polynomial = from_roots(&[4.0, -1.5, 2.0, 0.25]);
+
+        assert_roots(&polynomial.roots_in(-10.0, 10.0), &[-1.5, 0.25, 2.0, 4.0]);
+        assert_roots(&polynomial.roots_in(0.0, 3.0), &[0.25, 2.0]);
+        // Roots right on the ends of the range are included
+        assert_roots(&polynomial.roots_in(-1.5, 0.25), &[-1.5, 0.25]);
+    }
+
+    #[test]
+    fn test_roots_close_together() {
+        let polynomial = from_roots(&[3.75, 4.25, 5.75, 5.7501]);
+
+        assert_roots(&polynomial.roots_in(0.0, 10.0), &[3.75, 4.25, 5.75, 5.7501]);
+    }
+
+    #[test]
+    fn test_repeated_roots() {
+        // The polynomial touches zero at 1 without crossing it
+        let polynomial = from_roots(&[1.0, 1.0, -2.0, 3.0]);
+
+        assert_roots(&polynomial.roots_in(-5.0, 5.0), &[-2.0, 1.0, 3.0]);
+    }
+
+    #[test]
+    fn test_no_real_roots() {
+        // (x² + 1)(x² + 4)
+        let polynomial = Polynomial::new(&[4.0, 0.0, 5.0, 0.0, 1.0]);
+
+        assert!(polynomial.roots_in(-100.0, 100.0).is_empty());
+        assert!(Polynomial::new(&[2.0]).roots_in(-1.0, 1.0).is_empty());
+        assert!(Polynomial::new(&[]).roots_in(-1.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_linear_roots() {
+        let polynomial = Polynomial::new(&[-3.0, 2.0]);
+
+        assert_roots(&polynomial.roots_in(0.0, 2.0), &[1.5]);
+        assert!(polynomial.roots_in(2.0, 3.0).is_empty());
+    }
+}
@@ -0,0 +1,505 @@
+use crate::prelude::*;
+use std::fmt;
+use std::io::{self, Write};
+
+/// The kinds of Netpbm image a canvas can be written as
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PpmFormat {
+    /// P3, RGB written out as text
+    Plain,
+    /// P6, RGB written out as raw bytes
+    Binary,
+    /// P5, a binary grayscale image (PGM)
+    Graymap,
+}
+
+impl PpmFormat {
+    fn magic_number(self) -> &'static str {
+        match self {
+            PpmFormat::Plain => "P3",
+            PpmFormat::Binary => "P6",
+            PpmFormat::Graymap => "P5",
+        }
+    }
+}
+
+impl Canvas {
+    /// Streams the canvas out as a Netpbm image. Colors are clamped to 0..1 and scaled up to
+    /// `max_value`; binary images with a max value above 255 use two bytes per sample.
+    pub fn write_ppm<W: Write>(
+        &self,
+        writer: &mut W,
+        format: PpmFormat,
+        max_value: u16,
+    ) -> io::Result<()> {
+        if max_value == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "PPM max value must be at least 1",
+            ));
+        }
+        write!(
+            writer,
+            "{}\n{} {}\n{}\n",
+            format.magic_number(),
+            self.width,
+            self.height,
+            max_value
+        )?;
+
+        match format {
+            PpmFormat::Plain => self.write_plain_pixels(writer, max_value),
+            PpmFormat::Binary => {
+                for pixel in self.pixels.iter().flatten() {
+                    write_samples(writer, &pixel.to_scaled(max_value), max_value)?;
+                }
+                Ok(())
+            }
+            PpmFormat::Graymap => {
+                for pixel in self.pixels.iter().flatten() {
+                    let gray = scale_channel(pixel.luminance(), max_value);
+                    write_samples(writer, &[gray], max_value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes each row of the image starting on a new line, wrapping between samples so that no
+    /// line (including its newline) is longer than the 70 characters the format allows.
+    fn write_plain_pixels<W: Write>(&self, writer: &mut W, max_value: u16) -> io::Result<()> {
+        const MAX_LINE_LENGTH: usize = 70;
+
+        for row in &self.pixels {
+            let mut line_length = 0;
+
+            for sample in row.iter().flat_map(|pixel| pixel.to_scaled(max_value)) {
+                let sample = sample.to_string();
+                if line_length > 0 {
+                    if line_length + 1 + sample.len() >= MAX_LINE_LENGTH {
+                        writer.write_all(b"\n")?;
+                        line_length = 0;
+                    } else {
+                        writer.write_all(b" ")?;
+                        line_length += 1;
+                    }
+                }
+                writer.write_all(sample.as_bytes())?;
+                line_length += sample.len();
+            }
+
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    pub fn to_ppm_p6(&self) -> Vec<u8> {
+        let mut ppm = Vec::new();
+        self.write_ppm(&mut ppm, PpmFormat::Binary, 255)
+            .expect("Writing to a Vec can't fail");
+        ppm
+    }
+
+    pub fn to_pgm_p5(&self) -> Vec<u8> {
+        let mut pgm = Vec::new();
+        self.write_ppm(&mut pgm, PpmFormat::Graymap, 255)
+            .expect("Writing to a Vec can't fail");
+        pgm
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PpmError {
+    /// The magic number wasn't P3 or P6
+    UnsupportedFormat(String),
+    /// A header field or sample that isn't a valid number
+    InvalidNumber(String),
+    /// The max value has to be between 1 and 65535
+    InvalidMaxValue(u32),
+    SampleOutOfRange {
+        sample: u32,
+        max_value: u32,
+    },
+    /// The data stopped before every pixel had been read
+    UnexpectedEnd,
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpmError::UnsupportedFormat(magic) => write!(f, "Unsupported PPM format {:?}", magic),
+            PpmError::InvalidNumber(token) => write!(f, "{:?} is not a valid number", token),
+            PpmError::InvalidMaxValue(max_value) => write!(f, "Invalid max value {}", max_value),
+            PpmError::SampleOutOfRange { sample, max_value } => {
+                write!(
+                    f,
+                    "Sample {} is larger than the max value {}",
+                    sample, max_value
+                )
+            }
+            PpmError::UnexpectedEnd => write!(f, "PPM data ended early"),
+        }
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+impl Canvas {
+    /// Reads a plain (P3) or binary (P6) PPM image, scaling each sample by the file's
+    /// max value so the colors land in 0..1.
+    pub fn from_ppm(data: &[u8]) -> Result<Canvas, PpmError> {
+        let mut reader = PpmReader { data, position: 0 };
+
+        let magic = reader.next_token().ok_or(PpmError::UnexpectedEnd)?;
+        let format = match magic {
+            b"P3" => PpmFormat::Plain,
+            b"P6" => PpmFormat::Binary,
+            _ => {
+                let magic = String::from_utf8_lossy(magic).into_owned();
+                return Err(PpmError::UnsupportedFormat(magic));
+            }
+        };
+        let width = reader.next_dimension()?;
+        let height = reader.next_dimension()?;
+        let max_value = reader.next_number()?;
+        if max_value == 0 || max_value > u32::from(u16::MAX) {
+            return Err(PpmError::InvalidMaxValue(max_value));
+        }
+
+        let sample_count = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or(PpmError::UnexpectedEnd)?;
+        // Samples are read before the canvas is allocated, so a header claiming a huge
+        // image with no data behind it fails instead of exhausting memory
+        let samples = match format {
+            PpmFormat::Binary => reader.binary_samples(sample_count, max_value)?,
+            _ => reader.plain_samples(sample_count)?,
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        let scale = max_value as f32;
+        for (index, pixel) in samples.chunks_exact(3).enumerate() {
+            for sample in pixel {
+                if *sample > max_value {
+                    return Err(PpmError::SampleOutOfRange {
+                        sample: *sample,
+                        max_value,
+                    });
+                }
+            }
+            let x = (index % width as usize) as i32;
+            let y = (index / width as usize) as i32;
+            let color = Color::new(
+                pixel[0] as f32 / scale,
+                pixel[1] as f32 / scale,
+                pixel[2] as f32 / scale,
+            );
+            canvas.write_pixel(x, y, color);
+        }
+        Ok(canvas)
+    }
+}
+
+/// Walks through PPM data a token at a time, skipping whitespace and `#` comments
+pub(crate) struct PpmReader<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) position: usize,
+}
+
+impl<'a> PpmReader<'a> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(byte) = self.data.get(self.position) {
+            if *byte == b'#' {
+                while self.position < self.data.len() && self.data[self.position] != b'\n' {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub(crate) fn next_token(&mut self) -> Option<&'a [u8]> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while let Some(byte) = self.data.get(self.position) {
+            if byte.is_ascii_whitespace() || *byte == b'#' {
+                break;
+            }
+            self.position += 1;
+        }
+
+        if start == self.position {
+            None
+        } else {
+            Some(&self.data[start..self.position])
+        }
+    }
+
+    fn next_number(&mut self) -> Result<u32, PpmError> {
+        let token = self.next_token().ok_or(PpmError::UnexpectedEnd)?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| PpmError::InvalidNumber(String::from_utf8_lossy(token).into_owned()))
+    }
+
+    fn next_dimension(&mut self) -> Result<i32, PpmError> {
+        let dimension = self.next_number()?;
+        i32::try_from(dimension).map_err(|_| PpmError::InvalidNumber(dimension.to_string()))
+    }
+
+    fn plain_samples(&mut self, count: usize) -> Result<Vec<u32>, PpmError> {
+        let mut samples = Vec::new();
+        while samples.len() < count {
+            samples.push(self.next_number()?);
+        }
+        Ok(samples)
+    }
+
+    /// Binary data starts after the single whitespace character following the max value,
+    /// with samples above 255 taking up two big endian bytes.
+    fn binary_samples(&mut self, count: usize, max_value: u32) -> Result<Vec<u32>, PpmError> {
+        let bytes_per_sample = if max_value > 255 { 2 } else { 1 };
+        let start = self.position + 1;
+        let end = count
+            .checked_mul(bytes_per_sample)
+            .and_then(|length| length.checked_add(start))
+            .ok_or(PpmError::UnexpectedEnd)?;
+        let bytes = self.data.get(start..end).ok_or(PpmError::UnexpectedEnd)?;
+        self.position = end;
+
+        let samples = bytes
+            .chunks_exact(bytes_per_sample)
+            .map(|sample| match sample {
+                [high, low] => u32::from(u16::from_be_bytes([*high, *low])),
+                _ => u32::from(sample[0]),
+            });
+        Ok(samples.collect())
+    }
+}
+
+fn write_samples<W: Write>(writer: &mut W, samples: &[u16], max_value: u16) -> io::Result<()> {
+    for sample in samples {
+        if max_value > 255 {
+            writer.write_all(&sample.to_be_bytes())?;
+        } else {
+            writer.write_all(&[*sample as u8])?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod ppm_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_plain_ppm_scales_and_clamps() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.write_pixel(0, 0, Color::new(1.5, 0.0, 0.0));
+        canvas.write_pixel(1, 0, Color::new(0.0, 0.5, 0.0));
+        canvas.write_pixel(2, 0, Color::new(-0.5, 0.0, 1.0));
+
+        let expected = "P3\n3 1\n255\n255 0 0 0 128 0 0 0 255\n";
+        assert_eq!(canvas.to_pmm(), expected);
+    }
+
+    #[test]
+    fn test_plain_ppm_wraps_long_lines() {
+        let mut canvas = Canvas::new(10, 2);
+        for y in 0..2 {
+            for x in 0..10 {
+                canvas.write_pixel(x, y, Color::new(1.0, 0.8, 0.6));
+            }
+        }
+
+        let ppm = canvas.to_pmm();
+        let lines: Vec<&str> = ppm.lines().collect();
+        assert_eq!(
+            lines[3],
+            "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204"
+        );
+        assert_eq!(
+            lines[4],
+            "153 255 204 153 255 204 153 255 204 153 255 204 153"
+        );
+        assert_eq!(
+            lines[5],
+            "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204"
+        );
+        assert_eq!(
+            lines[6],
+            "153 255 204 153 255 204 153 255 204 153 255 204 153"
+        );
+        assert_eq!(lines.len(), 7);
+    }
+
+    #[test]
+    fn test_plain_ppm_line_lengths() {
+        let mut canvas = Canvas::new(37, 5);
+        for y in 0..5 {
+            for x in 0..37 {
+                canvas.write_pixel(x, y, Color::new(0.1 * y as f32, 1.0, 0.5));
+            }
+        }
+
+        let ppm = canvas.to_pmm();
+        assert!(ppm.ends_with('\n'));
+        for line in ppm.split_inclusive('\n') {
+            assert!(line.len() <= 70, "Line is too long: {}", line);
+            assert!(!line.starts_with(' ') && !line.ends_with(" \n"));
+        }
+    }
+
+    #[test]
+    fn test_binary_ppm() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.5, 0.0));
+
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend_from_slice(&[255, 128, 0, 0, 0, 0]);
+        assert_eq!(canvas.to_ppm_p6(), expected);
+    }
+
+    #[test]
+    fn test_binary_ppm_16_bit() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.5, 0.0));
+
+        let mut ppm = Vec::new();
+        canvas
+            .write_ppm(&mut ppm, PpmFormat::Binary, 65535)
+            .expect("Writing to a Vec can't fail");
+
+        let mut expected = b"P6\n1 1\n65535\n".to_vec();
+        expected.extend_from_slice(&[0xff, 0xff, 0x80, 0x00, 0x00, 0x00]);
+        assert_eq!(ppm, expected);
+    }
+
+    #[test]
+    fn test_graymap() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+        canvas.write_pixel(1, 0, Color::new(0.0, 1.0, 0.0));
+
+        let mut expected = b"P5\n2 1\n255\n".to_vec();
+        expected.extend_from_slice(&[255, 182]);
+        assert_eq!(canvas.to_pgm_p5(), expected);
+    }
+
+    #[test]
+    fn test_zero_max_value_is_rejected() {
+        let canvas = Canvas::new(1, 1);
+        let mut ppm = Vec::new();
+
+        assert!(canvas.write_ppm(&mut ppm, PpmFormat::Binary, 0).is_err());
+    }
+
+    #[test]
+    fn test_reading_rejects_unknown_formats() {
+        let ppm = b"P32\n1 1\n255\n0 0 0\n";
+
+        let error = Canvas::from_ppm(ppm).err();
+        assert_eq!(
+            error,
+            Some(PpmError::UnsupportedFormat(String::from("P32")))
+        );
+    }
+
+    #[test]
+    fn test_reading_plain_ppm() {
+        let ppm = b"P3\n4 3\n255\n\
+            255 127 0  0 127 255  127 255 0  255 255 255\n\
+            0 0 0  255 0 0  0 255 0  0 0 255\n\
+            255 255 0  0 255 255  255 0 255  127 127 127\n";
+        let canvas = Canvas::from_ppm(ppm).expect("Valid PPM");
+
+        assert_eq!(canvas.width, 4);
+        assert_eq!(canvas.height, 3);
+        assert!(canvas.pixel_at(0, 0).is_same(&Color::new(1.0, 0.498, 0.0)));
+        assert!(canvas.pixel_at(1, 0).is_same(&Color::new(0.0, 0.498, 1.0)));
+        assert!(canvas.pixel_at(3, 0).is_same(&Color::new(1.0, 1.0, 1.0)));
+        assert!(canvas.pixel_at(1, 1).is_same(&Color::new(1.0, 0.0, 0.0)));
+        assert!(canvas
+            .pixel_at(3, 2)
+            .is_same(&Color::new(0.498, 0.498, 0.498)));
+    }
+
+    #[test]
+    fn test_reading_comments_and_whitespace() {
+        let ppm = b"P3\n# this is a comment\n2 1\n# this, too\n255\n\
+            # another comment\n255 255 255\n# oh, no, comments in the pixel data!\n255 0 255\n";
+        let canvas = Canvas::from_ppm(ppm).expect("Valid PPM");
+
+        assert!(canvas.pixel_at(0, 0).is_same(&Color::new(1.0, 1.0, 1.0)));
+        assert!(canvas.pixel_at(1, 0).is_same(&Color::new(1.0, 0.0, 1.0)));
+
+        let ppm = b"P3   1\t1 \r\n 100#comment after the max value\n\n  100\n50\n\n25";
+        let canvas = Canvas::from_ppm(ppm).expect("Valid PPM");
+        assert!(canvas.pixel_at(0, 0).is_same(&Color::new(1.0, 0.5, 0.25)));
+    }
+
+    #[test]
+    fn test_reading_scales_by_max_value() {
+        let ppm = b"P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+        let canvas = Canvas::from_ppm(ppm).expect("Valid PPM");
+
+        assert!(canvas.pixel_at(0, 1).is_same(&Color::new(0.75, 0.5, 0.25)));
+    }
+
+    #[test]
+    fn test_reading_binary_ppm() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.write_pixel(2, 1, Color::new(0.2, 0.4, 0.6));
+
+        let read = Canvas::from_ppm(&canvas.to_ppm_p6()).expect("Valid PPM");
+        assert_eq!(read.width, 3);
+        assert_eq!(read.height, 2);
+        assert!(read.pixel_at(0, 0).is_same(&Color::new(1.0, 0.0, 0.0)));
+        assert!(read.pixel_at(2, 1).is_same(&Color::new(0.2, 0.4, 0.6)));
+
+        let mut ppm = Vec::new();
+        canvas
+            .write_ppm(&mut ppm, PpmFormat::Binary, 65535)
+            .expect("Writing to a Vec can't fail");
+        let read = Canvas::from_ppm(&ppm).expect("Valid 16 bit PPM");
+        assert!(read.pixel_at(2, 1).is_same(&Color::new(0.2, 0.4, 0.6)));
+    }
+
+    #[test]
+    fn test_reading_malformed_ppm() {
+        assert_eq!(Canvas::from_ppm(b"").err(), Some(PpmError::UnexpectedEnd));
+        assert_eq!(
+            Canvas::from_ppm(b"P3\n2 x\n255\n").err(),
+            Some(PpmError::InvalidNumber(String::from("x")))
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P3\n1 1\n0\n0 0 0").err(),
+            Some(PpmError::InvalidMaxValue(0))
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P3\n1 1\n255\n0 256 0").err(),
+            Some(PpmError::SampleOutOfRange {
+                sample: 256,
+                max_value: 255
+            })
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P3\n2 1\n255\n0 0 0\n").err(),
+            Some(PpmError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P6\n2 1\n255\n\x00\x00\x00\x00").err(),
+            Some(PpmError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P6\n100000 100000\n255\n").err(),
+            Some(PpmError::UnexpectedEnd)
+        );
+    }
+}
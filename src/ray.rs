@@ -0,0 +1,67 @@
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+    pub direction: Vector,
+}
+
+impl Ray {
+    pub fn new(origin: Point, direction: Vector) -> Self {
+        Self { origin, direction }
+    }
+
+    /// # Returns
+    /// The point reached after travelling `t` units along the ray's direction
+    pub fn position(&self, t: f32) -> Point {
+        self.origin + self.direction * t
+    }
+
+    /// # Returns
+    /// A new ray with both its origin and direction transformed by `matrix`.
+    /// The direction isn't normalized, so t values still line up with the original ray.
+    pub fn transform(&self, matrix: &Matrix4) -> Ray {
+        Ray::new(*matrix * self.origin, *matrix * self.direction)
+    }
+}
+
+#[cfg(test)]
+mod ray_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_creating_ray() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(4.0, 5.0, 6.0));
+
+        assert!(ray.origin.compare(&Point::new(1.0, 2.0, 3.0)));
+        assert!(ray.direction.is_same(&Vector::new(4.0, 5.0, 6.0)));
+    }
+
+    #[test]
+    fn test_ray_position() {
+        let ray = Ray::new(Point::new(2.0, 3.0, 4.0), Vector::new(1.0, 0.0, 0.0));
+
+        assert!(ray.position(0.0).compare(&Point::new(2.0, 3.0, 4.0)));
+        assert!(ray.position(1.0).compare(&Point::new(3.0, 3.0, 4.0)));
+        assert!(ray.position(-1.0).compare(&Point::new(1.0, 3.0, 4.0)));
+        assert!(ray.position(2.5).compare(&Point::new(4.5, 3.0, 4.0)));
+    }
+
+    #[test]
+    fn test_translating_ray() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+        let transformed = ray.transform(&Matrix4::translation(3.0, 4.0, 5.0));
+
+        assert!(transformed.origin.compare(&Point::new(4.0, 6.0, 8.0)));
+        assert!(transformed.direction.is_same(&Vector::new(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_scaling_ray() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+        let transformed = ray.transform(&Matrix4::scaling(2.0, 3.0, 4.0));
+
+        assert!(transformed.origin.compare(&Point::new(2.0, 6.0, 12.0)));
+        assert!(transformed.direction.is_same(&Vector::new(0.0, 3.0, 0.0)));
+    }
+}
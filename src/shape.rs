@@ -0,0 +1,225 @@
+use crate::prelude::*;
+
+/// An axis aligned box, in object space, that a shape fits entirely inside. Shapes that go on
+/// forever use infinite extents.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        let (min, max, point) = (self.min.position, self.max.position, point.position);
+        (min.x..=max.x).contains(&point.x)
+            && (min.y..=max.y).contains(&point.y)
+            && (min.z..=max.z).contains(&point.z)
+    }
+
+    /// # Returns
+    /// The t values where the ray enters and leaves the box, if it hits it at all.
+    /// The ray is hitting the box where it's between all three pairs of slabs at once.
+    pub fn intersect(&self, ray: &Ray) -> Option<(f32, f32)> {
+        let (origin, direction) = (ray.origin.position, ray.direction);
+        let (min, max) = (self.min.position, self.max.position);
+        let slabs = [
+            slab_intersection(origin.x, direction.x, min.x, max.x),
+            slab_intersection(origin.y, direction.y, min.y, max.y),
+            slab_intersection(origin.z, direction.z, min.z, max.z),
+        ];
+
+        // f32::max and min skip the NaN that a ray lying exactly on a slab's plane gives
+        let t_min = slabs
+            .iter()
+            .fold(f32::NEG_INFINITY, |t, slab| t.max(slab.0));
+        let t_max = slabs.iter().fold(f32::INFINITY, |t, slab| t.min(slab.1));
+        (t_min <= t_max).then_some((t_min, t_max))
+    }
+}
+
+/// # Returns
+/// The t values where a ray enters and leaves the slab between `min` and `max` on one axis.
+/// A ray parallel to the slab divides by zero, giving infinities of the right sign when it's
+/// between the two planes and an empty range when it isn't.
+fn slab_intersection(origin: f32, direction: f32, min: f32, max: f32) -> (f32, f32) {
+    let t_min = (min - origin) / direction;
+    let t_max = (max - origin) / direction;
+    if t_min > t_max {
+        (t_max, t_min)
+    } else {
+        (t_min, t_max)
+    }
+}
+
+/// Anything a ray can hit. A shape only has to know how to intersect and find normals in its
+/// own object space, `intersect` and `normal_at` take care of moving between that and the
+/// world with the transform.
+pub trait Shape {
+    /// Moves, scales and rotates the shape from object space into place in the world
+    fn transform(&self) -> &Transform;
+    fn material(&self) -> &Material;
+    fn material_mut(&mut self) -> &mut Material;
+
+    /// # Returns
+    /// Every point where a ray, already in object space, crosses the surface of the shape
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_>;
+    /// # Returns
+    /// The normal at a point on the surface, both in object space. It doesn't need to be
+    /// normalized.
+    fn local_normal_at(&self, point: Point) -> Vector;
+    /// # Returns
+    /// The box the shape fits inside, in object space
+    fn bounds(&self) -> Bounds;
+
+    /// # Returns
+    /// Every point where the ray crosses the surface of the shape, sorted by t
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        // Transforming the ray by the inverse is the same as transforming the shape
+        self.local_intersect(&ray.transform(self.transform().inverse()))
+    }
+
+    /// # Returns
+    /// The normalized vector pointing straight out of the shape at a point on its surface.
+    /// The normal is found in object space, then brought back to world space with the
+    /// inverse transpose so that it stays perpendicular to a scaled or sheared surface.
+    fn normal_at(&self, world_point: Point) -> Vector {
+        let inverse = self.transform().inverse();
+        let object_normal = self.local_normal_at(*inverse * world_point);
+
+        let mut world_normal = inverse.transposed() * object_normal;
+        world_normal.normalize();
+        world_normal
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod shape_tests {
+    use crate::prelude::*;
+    use std::cell::Cell;
+
+    /// A ray with its direction normalized, from plain tuples to keep tables of cases short
+    pub(crate) fn ray(origin: (f32, f32, f32), direction: (f32, f32, f32)) -> Ray {
+        let mut direction = Vector::new(direction.0, direction.1, direction.2);
+        direction.normalize();
+        Ray::new(Point::new(origin.0, origin.1, origin.2), direction)
+    }
+
+    /// A shape that never gets hit, but remembers the last ray it was asked to intersect and
+    /// uses the point itself as the normal
+    struct TestShape {
+        transform: Transform,
+        material: Material,
+        saved_ray: Cell<Option<Ray>>,
+    }
+
+    impl TestShape {
+        fn new(transform: Matrix4) -> Self {
+            Self {
+                transform: Transform::new(transform).unwrap(),
+                material: Material::default(),
+                saved_ray: Cell::new(None),
+            }
+        }
+    }
+
+    impl Shape for TestShape {
+        fn transform(&self) -> &Transform {
+            &self.transform
+        }
+        fn material(&self) -> &Material {
+            &self.material
+        }
+        fn material_mut(&mut self) -> &mut Material {
+            &mut self.material
+        }
+        fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+            self.saved_ray.set(Some(*ray));
+            Intersections::new(vec![])
+        }
+        fn local_normal_at(&self, point: Point) -> Vector {
+            point.position
+        }
+        fn bounds(&self) -> Bounds {
+            Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+        }
+    }
+
+    #[test]
+    fn test_intersecting_scaled_shape() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = TestShape::new(Matrix4::scaling(2.0, 2.0, 2.0));
+        shape.intersect(&ray);
+
+        let local_ray = shape.saved_ray.get().expect("The ray was passed on");
+        assert!(local_ray.origin.compare(&Point::new(0.0, 0.0, -2.5)));
+        assert!(local_ray.direction.is_same(&Vector::new(0.0, 0.0, 0.5)));
+    }
+
+    #[test]
+    fn test_intersecting_translated_shape() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = TestShape::new(Matrix4::translation(5.0, 0.0, 0.0));
+        shape.intersect(&ray);
+
+        let local_ray = shape.saved_ray.get().expect("The ray was passed on");
+        assert!(local_ray.origin.compare(&Point::new(-5.0, 0.0, -5.0)));
+        assert!(local_ray.direction.is_same(&Vector::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_normal_on_translated_shape() {
+        let shape = TestShape::new(Matrix4::translation(0.0, 1.0, 0.0));
+        let half_root_two = f32::sqrt(2.0) / 2.0;
+        let normal = shape.normal_at(Point::new(0.0, 1.0 + half_root_two, -half_root_two));
+
+        assert!(normal.is_same(&Vector::new(0.0, half_root_two, -half_root_two)));
+    }
+
+    #[test]
+    fn test_normal_on_transformed_shape() {
+        let matrix = Matrix4::from_identity()
+            .rotate_z(std::f32::consts::PI / 5.0)
+            .scale(1.0, 0.5, 1.0);
+        let shape = TestShape::new(matrix);
+        let half_root_two = f32::sqrt(2.0) / 2.0;
+        let normal = shape.normal_at(Point::new(0.0, half_root_two, -half_root_two));
+
+        assert!(normal.is_same(&Vector::new(0.0, 0.97014, -0.24254)));
+    }
+
+    #[test]
+    fn test_shapes_share_one_interface() {
+        let mut shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new(Sphere::new()),
+            Box::new(TestShape::new(Matrix4::from_identity())),
+        ];
+        shapes[1].material_mut().ambient = 1.0;
+
+        assert_eq!(shapes[0].material().ambient, 0.1);
+        assert_eq!(shapes[1].material().ambient, 1.0);
+        assert!(shapes[0].bounds().contains(&Point::new(0.0, 1.0, 0.0)));
+        assert!(!shapes[0].bounds().contains(&Point::new(0.0, 1.5, 0.0)));
+    }
+
+    #[test]
+    fn test_bounds_as_bounding_volume() {
+        let bounds = Bounds::new(Point::new(0.0, 0.0, 0.0), Point::new(2.0, 4.0, 2.0));
+
+        let ray = Ray::new(Point::new(1.0, 2.0, -3.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(bounds.intersect(&ray), Some((3.0, 5.0)));
+        let ray = Ray::new(Point::new(3.0, 2.0, -3.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(bounds.intersect(&ray), None);
+        // A box that's already behind the ray still counts, like a shape behind it would
+        let ray = Ray::new(Point::new(1.0, 2.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(bounds.intersect(&ray), Some((-5.0, -3.0)));
+
+        // Infinite bounds, like a plane's, are hit by anything that isn't parallel
+        let plane = Plane::new().bounds();
+        let ray = Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(plane.intersect(&ray), Some((1.0, 1.0)));
+    }
+}
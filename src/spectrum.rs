@@ -0,0 +1,402 @@
+//! Colors as light across the visible spectrum instead of three RGB channels, for effects
+//! that depend on wavelength like dispersion and the tint of metals
+use crate::prelude::*;
+use std::ops::{Add, Mul};
+
+/// The shortest wavelength sampled, in nanometers
+pub const LAMBDA_MIN: f32 = 380.0;
+/// The longest wavelength sampled, in nanometers
+pub const LAMBDA_MAX: f32 = 720.0;
+/// One sample every 10nm
+pub const SPECTRUM_SAMPLES: usize = 35;
+const SAMPLE_SPACING: f32 = (LAMBDA_MAX - LAMBDA_MIN) / (SPECTRUM_SAMPLES - 1) as f32;
+
+/// Brian Smits' basis spectra for converting RGB reflectances, sampled at 10 evenly spaced
+/// wavelengths from 380nm to 720nm. See "An RGB-to-Spectrum Conversion for Reflectances".
+const SMITS_WHITE: [f32; 10] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f32; 10] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f32; 10] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 0.9959, 0.9840,
+];
+const SMITS_YELLOW: [f32; 10] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f32; 10] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f32; 10] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f32; 10] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// A piecewise Gaussian, wider on one side of its peak than the other
+fn lobe(wavelength: f32, peak: f32, width_below: f32, width_above: f32) -> f32 {
+    let width = if wavelength < peak {
+        width_below
+    } else {
+        width_above
+    };
+    let distance = (wavelength - peak) / width;
+    (-0.5 * distance * distance).exp()
+}
+
+/// The CIE 1931 2° color matching functions, using the multi lobe fit from Wyman, Sloan and
+/// Shirley's "Simple Analytic Approximations to the CIE XYZ Color Matching Functions"
+pub fn color_matching(wavelength: f32) -> (f32, f32, f32) {
+    let x = 1.056 * lobe(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * lobe(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * lobe(wavelength, 501.1, 20.4, 26.2);
+    let y =
+        0.821 * lobe(wavelength, 568.8, 46.9, 40.5) + 0.286 * lobe(wavelength, 530.9, 16.3, 31.1);
+    let z =
+        1.217 * lobe(wavelength, 437.0, 11.8, 36.0) + 0.681 * lobe(wavelength, 459.0, 26.0, 13.8);
+    (x, y, z)
+}
+
+/// Converts CIE XYZ to linear sRGB with its D65 white point
+fn xyz_to_linear_srgb(x: f32, y: f32, z: f32) -> Color {
+    Color::new(
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    )
+}
+
+/// Linearly interpolates `values`, spread evenly from `LAMBDA_MIN` to `LAMBDA_MAX`, at a
+/// wavelength. Wavelengths outside the range take the nearest end's value.
+fn interpolate(values: &[f32], wavelength: f32) -> f32 {
+    let spacing = (LAMBDA_MAX - LAMBDA_MIN) / (values.len() - 1) as f32;
+    let position = ((wavelength - LAMBDA_MIN) / spacing).clamp(0.0, (values.len() - 1) as f32);
+    let index = (position as usize).min(values.len() - 2);
+    let t = position - index as f32;
+    values[index] * (1.0 - t) + values[index + 1] * t
+}
+
+/// Light or reflectance sampled at `SPECTRUM_SAMPLES` evenly spaced visible wavelengths
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spectrum {
+    samples: [f32; SPECTRUM_SAMPLES],
+}
+
+impl Spectrum {
+    pub fn constant(value: f32) -> Self {
+        Self {
+            samples: [value; SPECTRUM_SAMPLES],
+        }
+    }
+
+    /// Samples `function`, which is given each wavelength in nanometers
+    pub fn from_fn<F: Fn(f32) -> f32>(function: F) -> Self {
+        let mut samples = [0.0; SPECTRUM_SAMPLES];
+        for (index, sample) in samples.iter_mut().enumerate() {
+            *sample = function(Self::wavelength(index));
+        }
+        Self { samples }
+    }
+
+    /// Builds a smooth reflectance spectrum that looks like `color`, with Smits' method of
+    /// mixing white with the secondary and then primary colors that make up the difference.
+    /// Channels are expected to be in 0..1.
+    pub fn from_color(color: Color) -> Self {
+        let Color { red, green, blue } = color;
+        let basis = |values: &[f32; 10]| Spectrum::from_fn(|lambda| interpolate(values, lambda));
+        let (white, cyan, magenta, yellow) = (
+            basis(&SMITS_WHITE),
+            basis(&SMITS_CYAN),
+            basis(&SMITS_MAGENTA),
+            basis(&SMITS_YELLOW),
+        );
+        let (red_basis, green_basis, blue_basis) =
+            (basis(&SMITS_RED), basis(&SMITS_GREEN), basis(&SMITS_BLUE));
+
+        if red <= green && red <= blue {
+            let spectrum = white * red;
+            if green <= blue {
+                spectrum + cyan * (green - red) + blue_basis * (blue - green)
+            } else {
+                spectrum + cyan * (blue - red) + green_basis * (green - blue)
+            }
+        } else if green <= red && green <= blue {
+            let spectrum = white * green;
+            if red <= blue {
+                spectrum + magenta * (red - green) + blue_basis * (blue - red)
+            } else {
+                spectrum + magenta * (blue - green) + red_basis * (red - blue)
+            }
+        } else {
+            let spectrum = white * blue;
+            if red <= green {
+                spectrum + yellow * (red - blue) + green_basis * (green - red)
+            } else {
+                spectrum + yellow * (green - blue) + red_basis * (red - green)
+            }
+        }
+    }
+
+    /// # Returns
+    /// The wavelength in nanometers of sample `index`
+    pub fn wavelength(index: usize) -> f32 {
+        LAMBDA_MIN + index as f32 * SAMPLE_SPACING
+    }
+
+    /// # Returns
+    /// The spectrum's value at any wavelength, interpolating between samples
+    pub fn value_at(&self, wavelength: f32) -> f32 {
+        interpolate(&self.samples, wavelength)
+    }
+
+    /// Integrates the spectrum against the color matching functions, scaled so that the
+    /// constant spectrum of 1 has a luminance (Y) of 1
+    pub fn to_xyz(self) -> (f32, f32, f32) {
+        let mut totals = (0.0, 0.0, 0.0);
+        let mut luminance_total = 0.0;
+        for (index, sample) in self.samples.iter().enumerate() {
+            let (x, y, z) = color_matching(Self::wavelength(index));
+            totals.0 += x * sample;
+            totals.1 += y * sample;
+            totals.2 += z * sample;
+            luminance_total += y;
+        }
+        (
+            totals.0 / luminance_total,
+            totals.1 / luminance_total,
+            totals.2 / luminance_total,
+        )
+    }
+
+    /// Converts to a linear RGB color. The lights in a scene are spectrally flat, so the
+    /// result is white balanced to make the constant spectrum come out a neutral gray rather
+    /// than the pink equal energy light has next to sRGB's D65 white.
+    pub fn to_color(self) -> Color {
+        let (x, y, z) = self.to_xyz();
+        xyz_to_linear_srgb(x, y, z) * white_balance()
+    }
+}
+
+/// The per channel scale that turns the sRGB color of the constant spectrum into white
+fn white_balance() -> Color {
+    let (x, y, z) = Spectrum::constant(1.0).to_xyz();
+    let white = xyz_to_linear_srgb(x, y, z);
+    Color::new(1.0 / white.red, 1.0 / white.green, 1.0 / white.blue)
+}
+
+/// # Returns
+/// The linear RGB contribution of light at a single wavelength, for renders that trace each
+/// ray at one randomly chosen wavelength. Averaging it over wavelengths spread evenly across
+/// the spectrum gives white.
+pub fn wavelength_to_color(wavelength: f32) -> Color {
+    let luminance_total: f32 = (0..SPECTRUM_SAMPLES)
+        .map(|index| color_matching(Spectrum::wavelength(index)).1)
+        .sum();
+    let (x, y, z) = color_matching(wavelength);
+    let scale = SPECTRUM_SAMPLES as f32 / luminance_total;
+    xyz_to_linear_srgb(x * scale, y * scale, z * scale) * white_balance()
+}
+
+impl Add for Spectrum {
+    type Output = Spectrum;
+
+    fn add(self, rhs: Spectrum) -> Self::Output {
+        let mut samples = self.samples;
+        for (sample, other) in samples.iter_mut().zip(rhs.samples) {
+            *sample += other;
+        }
+        Spectrum { samples }
+    }
+}
+
+/// Multiplying two spectra filters one by the other, wavelength by wavelength
+impl Mul for Spectrum {
+    type Output = Spectrum;
+
+    fn mul(self, rhs: Spectrum) -> Self::Output {
+        let mut samples = self.samples;
+        for (sample, other) in samples.iter_mut().zip(rhs.samples) {
+            *sample *= other;
+        }
+        Spectrum { samples }
+    }
+}
+
+impl Mul<f32> for Spectrum {
+    type Output = Spectrum;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Spectrum {
+            samples: self.samples.map(|sample| sample * rhs),
+        }
+    }
+}
+
+/// Cauchy's equation `n = A + B / λ²` for how a transparent material's index of refraction
+/// changes with wavelength, with λ in micrometers. A larger B splits colors further apart.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cauchy {
+    pub a: f32,
+    /// In square micrometers
+    pub b: f32,
+}
+
+impl Cauchy {
+    /// Borosilicate crown glass, the usual glass for lenses
+    pub const BK7: Cauchy = Cauchy {
+        a: 1.5046,
+        b: 0.004_20,
+    };
+    /// Fused silica, pure glassy quartz that disperses light less than any common glass
+    pub const FUSED_SILICA: Cauchy = Cauchy {
+        a: 1.4580,
+        b: 0.003_54,
+    };
+    /// Dense flint glass, which disperses light far more than crown glass
+    pub const DENSE_FLINT: Cauchy = Cauchy {
+        a: 1.7280,
+        b: 0.013_42,
+    };
+
+    /// # Returns
+    /// The index of refraction for light of a wavelength in nanometers
+    pub fn ior_at(&self, wavelength: f32) -> f32 {
+        let micrometers = wavelength / 1000.0;
+        self.a + self.b / (micrometers * micrometers)
+    }
+
+    pub fn ior_spectrum(&self) -> Spectrum {
+        Spectrum::from_fn(|wavelength| self.ior_at(wavelength))
+    }
+}
+
+/// # Returns
+/// How much light a metal reflects head on at each wavelength, from its index of refraction
+/// `eta` and absorption `k`, which is what gives gold and copper their color
+pub fn conductor_reflectance(eta: &Spectrum, k: &Spectrum) -> Spectrum {
+    let mut samples = [0.0; SPECTRUM_SAMPLES];
+    for (index, sample) in samples.iter_mut().enumerate() {
+        let (eta, k) = (eta.samples[index], k.samples[index]);
+        let absorption = k * k;
+        *sample = ((eta - 1.0).powi(2) + absorption) / ((eta + 1.0).powi(2) + absorption);
+    }
+    Spectrum { samples }
+}
+
+#[cfg(test)]
+mod spectrum_tests {
+    use crate::compare_float;
+    use crate::spectrum::*;
+
+    fn is_close(a: Color, b: Color, tolerance: f32) -> bool {
+        [a.red - b.red, a.green - b.green, a.blue - b.blue]
+            .iter()
+            .all(|difference| difference.abs() <= tolerance)
+    }
+
+    #[test]
+    fn test_wavelengths() {
+        assert_eq!(Spectrum::wavelength(0), LAMBDA_MIN);
+        assert_eq!(Spectrum::wavelength(SPECTRUM_SAMPLES - 1), LAMBDA_MAX);
+        assert!(compare_float(Spectrum::wavelength(17), 550.0));
+
+        let ramp = Spectrum::from_fn(|wavelength| wavelength / 100.0);
+        assert!(compare_float(ramp.value_at(555.0), 5.55));
+        assert!(compare_float(ramp.value_at(300.0), 3.8));
+        assert!(compare_float(ramp.value_at(800.0), 7.2));
+    }
+
+    #[test]
+    fn test_color_matching_peaks() {
+        // The real functions peak at about 599nm, 555nm and 446nm
+        let (x, _, _) = color_matching(599.0);
+        let (_, y, _) = color_matching(556.0);
+        let (_, _, z) = color_matching(446.0);
+        assert!(compare_float(x, 1.06));
+        assert!(compare_float(y, 1.0));
+        assert!(z > 1.7 && z < 1.8);
+        assert!(color_matching(700.0).1 < 0.01);
+    }
+
+    #[test]
+    fn test_constant_spectrum_is_gray() {
+        let (_, y, _) = Spectrum::constant(1.0).to_xyz();
+        assert!(compare_float(y, 1.0));
+        assert!(is_close(
+            Spectrum::constant(1.0).to_color(),
+            Color::WHITE,
+            1e-5
+        ));
+        assert!(is_close(
+            Spectrum::constant(0.25).to_color(),
+            Color::new(0.25, 0.25, 0.25),
+            1e-5
+        ));
+    }
+
+    #[test]
+    fn test_rgb_round_trip() {
+        let colors = [
+            Color::WHITE,
+            Color::RED,
+            Color::new(0.8, 0.6, 0.2),
+            Color::new(0.1, 0.5, 0.9),
+            Color::new(0.3, 0.9, 0.4),
+        ];
+        for color in colors {
+            let spectrum = Spectrum::from_color(color);
+            assert!(
+                is_close(spectrum.to_color(), color, 0.1),
+                "{:?} came back as {:?}",
+                color,
+                spectrum.to_color()
+            );
+        }
+        // Reflectances built from the basis stay physical
+        let spectrum = Spectrum::from_color(Color::new(0.2, 0.9, 0.4));
+        assert!((0..SPECTRUM_SAMPLES).all(|index| spectrum.samples[index] >= 0.0));
+    }
+
+    #[test]
+    fn test_single_wavelengths() {
+        let average = (0..SPECTRUM_SAMPLES)
+            .map(|index| wavelength_to_color(Spectrum::wavelength(index)))
+            .fold(Color::BLACK, |total, color| total + color)
+            / SPECTRUM_SAMPLES as f32;
+        assert!(is_close(average, Color::WHITE, 1e-4));
+
+        let red = wavelength_to_color(650.0);
+        assert!(red.red > red.green && red.red > red.blue);
+        let blue = wavelength_to_color(450.0);
+        assert!(blue.blue > blue.red && blue.blue > blue.green);
+    }
+
+    #[test]
+    fn test_spectrum_operators() {
+        let a = Spectrum::constant(0.5);
+        let b = Spectrum::from_fn(|wavelength| wavelength / 1000.0);
+        assert!(compare_float((a + b).value_at(500.0), 1.0));
+        assert!(compare_float((a * b).value_at(600.0), 0.3));
+        assert!(compare_float((b * 2.0).value_at(400.0), 0.8));
+    }
+
+    #[test]
+    fn test_cauchy_dispersion() {
+        assert!(compare_float(Cauchy::BK7.ior_at(587.6), 1.5168));
+        assert!(compare_float(Cauchy::FUSED_SILICA.ior_at(587.6), 1.4682));
+        // Glass bends blue light more than red
+        let ior = Cauchy::DENSE_FLINT.ior_spectrum();
+        assert!(ior.value_at(LAMBDA_MIN) > ior.value_at(LAMBDA_MAX));
+    }
+
+    #[test]
+    fn test_conductor_reflectance() {
+        let glass = conductor_reflectance(&Spectrum::constant(1.5), &Spectrum::constant(0.0));
+        assert!(compare_float(glass.value_at(500.0), 0.04));
+
+        let absorbing = conductor_reflectance(&Spectrum::constant(0.2), &Spectrum::constant(3.0));
+        assert!(absorbing.value_at(500.0) > 0.9);
+    }
+}
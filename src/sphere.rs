@@ -0,0 +1,208 @@
+use crate::prelude::*;
+
+/// A sphere of radius 1 centred on its origin
+pub struct Sphere {
+    pub origin: Point,
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Sphere {
+    pub fn new() -> Self {
+        Self {
+            origin: Point::zero(),
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+}
+
+impl Default for Sphere {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Sphere {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    fn material(&self) -> &Material {
+        &self.material
+    }
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    /// A ray that only grazes the sphere still returns two (equal) intersections
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let sphere_to_ray = ray.origin - self.origin;
+
+        let a = ray.direction.dot_product(&ray.direction);
+        let b = 2.0 * ray.direction.dot_product(&sphere_to_ray);
+        let c = sphere_to_ray.dot_product(&sphere_to_ray) - 1.0;
+
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return Intersections::new(vec![]);
+        }
+
+        let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+        let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+        Intersections::new(vec![
+            Intersection::new(t1, self),
+            Intersection::new(t2, self),
+        ])
+    }
+
+    fn local_normal_at(&self, point: Point) -> Vector {
+        point - self.origin
+    }
+
+    fn bounds(&self) -> Bounds {
+        let radius = Vector::new(1.0, 1.0, 1.0);
+        Bounds::new(self.origin - radius, self.origin + radius)
+    }
+}
+
+#[cfg(test)]
+mod sphere_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_ray_intersects_sphere_at_two_points() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, 4.0));
+        assert!(compare_float(xs[1].t, 6.0));
+    }
+
+    #[test]
+    fn test_ray_intersects_sphere_at_tangent() {
+        let ray = Ray::new(Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, 5.0));
+        assert!(compare_float(xs[1].t, 5.0));
+    }
+
+    #[test]
+    fn test_ray_misses_sphere() {
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+        let xs = sphere.intersect(&ray);
+
+        assert!(xs.is_empty());
+    }
+
+    #[test]
+    fn test_ray_originates_inside_sphere() {
+        let ray = Ray::new(Point::zero(), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, -1.0));
+        assert!(compare_float(xs[1].t, 1.0));
+    }
+
+    #[test]
+    fn test_sphere_behind_ray() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, -6.0));
+        assert!(compare_float(xs[1].t, -4.0));
+        assert!(std::ptr::addr_eq(xs[0].object, &sphere));
+        assert!(std::ptr::addr_eq(xs[1].object, &sphere));
+    }
+
+    #[test]
+    fn test_sphere_material() {
+        let mut sphere = Sphere::new();
+        assert_eq!(sphere.material, Material::default());
+
+        sphere.material.ambient = 1.0;
+        assert_eq!(sphere.material.ambient, 1.0);
+    }
+
+    #[test]
+    fn test_default_transform() {
+        let sphere = Sphere::new();
+        assert!(sphere.transform.matrix().equals(&Matrix4::from_identity()));
+    }
+
+    #[test]
+    fn test_intersecting_scaled_sphere() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut sphere = Sphere::new();
+        sphere.transform = Transform::new(Matrix4::scaling(2.0, 2.0, 2.0)).unwrap();
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert!(compare_float(xs[0].t, 3.0));
+        assert!(compare_float(xs[1].t, 7.0));
+    }
+
+    #[test]
+    fn test_intersecting_translated_sphere() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut sphere = Sphere::new();
+        sphere.transform = Transform::new(Matrix4::translation(5.0, 0.0, 0.0)).unwrap();
+
+        assert!(sphere.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn test_normals_on_axes() {
+        let sphere = Sphere::new();
+
+        let normal = sphere.normal_at(Point::new(1.0, 0.0, 0.0));
+        assert!(normal.is_same(&Vector::new(1.0, 0.0, 0.0)));
+        let normal = sphere.normal_at(Point::new(0.0, 1.0, 0.0));
+        assert!(normal.is_same(&Vector::new(0.0, 1.0, 0.0)));
+        let normal = sphere.normal_at(Point::new(0.0, 0.0, 1.0));
+        assert!(normal.is_same(&Vector::new(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_normal_is_normalized() {
+        let sphere = Sphere::new();
+        let third = f32::sqrt(3.0) / 3.0;
+        let normal = sphere.normal_at(Point::new(third, third, third));
+
+        assert!(normal.is_same(&Vector::new(third, third, third)));
+        assert!(compare_float(normal.magnitude(), 1.0));
+    }
+
+    #[test]
+    fn test_normal_on_translated_sphere() {
+        let mut sphere = Sphere::new();
+        sphere.transform = Transform::new(Matrix4::translation(0.0, 1.0, 0.0)).unwrap();
+        let half_root_two = f32::sqrt(2.0) / 2.0;
+        let normal = sphere.normal_at(Point::new(0.0, 1.0 + half_root_two, -half_root_two));
+
+        assert!(normal.is_same(&Vector::new(0.0, half_root_two, -half_root_two)));
+    }
+
+    #[test]
+    fn test_normal_on_transformed_sphere() {
+        let mut sphere = Sphere::new();
+        let matrix = Matrix4::from_identity()
+            .rotate_z(std::f32::consts::PI / 5.0)
+            .scale(1.0, 0.5, 1.0);
+        sphere.transform = Transform::new(matrix).unwrap();
+        let half_root_two = f32::sqrt(2.0) / 2.0;
+        let normal = sphere.normal_at(Point::new(0.0, half_root_two, -half_root_two));
+
+        assert!(normal.is_same(&Vector::new(0.0, 0.97014, -0.24254)));
+    }
+}
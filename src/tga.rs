@@ -0,0 +1,163 @@
+//! Truevision TGA images, see <http://www.paulbourke.net/dataformats/tga/>
+use crate::prelude::*;
+use std::io::{self, Write};
+
+/// How the pixel data of a TGA image is stored
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TgaEncoding {
+    /// Every pixel written out as is
+    Raw,
+    /// Packets of repeated or literal pixels, never crossing from one row to the next
+    RunLength,
+}
+
+impl TgaEncoding {
+    /// The image type for true color images in each encoding
+    fn image_type(self) -> u8 {
+        match self {
+            TgaEncoding::Raw => 2,
+            TgaEncoding::RunLength => 10,
+        }
+    }
+}
+
+/// The most pixels a single packet can hold
+const MAX_PACKET: usize = 128;
+/// Marks the file as TGA 2.0, after the (unused) extension and developer area offsets
+const FOOTER: &[u8; 26] = b"\0\0\0\0\0\0\0\0TRUEVISION-XFILE.\0";
+
+impl Canvas {
+    /// Streams the canvas out as a top down TGA: 24 bit BGR, or 32 bit BGRA if the canvas has
+    /// an alpha channel. Colors are clamped to 0..1 the same way as for PPM output.
+    pub fn write_tga<W: Write>(&self, writer: &mut W, encoding: TgaEncoding) -> io::Result<()> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "Image too large for TGA");
+        let width = u16::try_from(self.width).map_err(|_| too_large())?;
+        let height = u16::try_from(self.height).map_err(|_| too_large())?;
+        let (bytes_per_pixel, alpha_bits) = if self.has_alpha() { (4, 8) } else { (3, 0) };
+        // Bit 5 puts the origin at the top left, so rows are written in canvas order
+        let descriptor = alpha_bits | 0x20;
+
+        // No image ID or color map, and the image sits at 0, 0
+        let mut header = vec![0, 0, encoding.image_type(), 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        header.extend(width.to_le_bytes());
+        header.extend(height.to_le_bytes());
+        header.extend([bytes_per_pixel as u8 * 8, descriptor]);
+        writer.write_all(&header)?;
+
+        for row in self.rows_8_bit() {
+            let pixels: Vec<Vec<u8>> = row
+                .chunks_exact(bytes_per_pixel)
+                .map(|pixel| {
+                    let mut bgra = vec![pixel[2], pixel[1], pixel[0]];
+                    bgra.extend(pixel.get(3));
+                    bgra
+                })
+                .collect();
+            match encoding {
+                TgaEncoding::Raw => writer.write_all(&pixels.concat())?,
+                TgaEncoding::RunLength => write_run_length_encoded(writer, &pixels)?,
+            }
+        }
+        writer.write_all(FOOTER)
+    }
+
+    pub fn to_tga(&self, encoding: TgaEncoding) -> Vec<u8> {
+        let mut tga = Vec::new();
+        self.write_tga(&mut tga, encoding)
+            .expect("Writing to a Vec can't fail");
+        tga
+    }
+}
+
+/// Writes a row as run packets (the high bit set plus one less than the repeat count, then the
+/// pixel) and raw packets (one less than the pixel count, then the pixels)
+fn write_run_length_encoded<W: Write>(writer: &mut W, pixels: &[Vec<u8>]) -> io::Result<()> {
+    let mut position = 0;
+    while position < pixels.len() {
+        let run = pixels[position..]
+            .iter()
+            .take(MAX_PACKET)
+            .take_while(|pixel| **pixel == pixels[position])
+            .count();
+        if run > 1 {
+            writer.write_all(&[0x80 | (run - 1) as u8])?;
+            writer.write_all(&pixels[position])?;
+            position += run;
+            continue;
+        }
+
+        // A raw packet runs until the next pair of repeated pixels
+        let mut end = position + 1;
+        while end < pixels.len() && end - position < MAX_PACKET && pixels[end] != pixels[end - 1] {
+            end += 1;
+        }
+        if end < pixels.len() && pixels[end] == pixels[end - 1] {
+            end -= 1;
+        }
+        writer.write_all(&[(end - position - 1) as u8])?;
+        writer.write_all(&pixels[position..end].concat())?;
+        position = end;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tga_tests {
+    use crate::tga::*;
+
+    #[test]
+    fn test_raw_tga() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.write_pixel(1, 1, Color::new(0.0, 0.5, 1.0));
+
+        let tga = canvas.to_tga(TgaEncoding::Raw);
+        assert_eq!(
+            tga[..18],
+            [0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 24, 0x20]
+        );
+        assert_eq!(tga[18..30], [0, 0, 255, 0, 0, 0, 0, 0, 0, 255, 128, 0]);
+        assert_eq!(&tga[30..], FOOTER);
+    }
+
+    #[test]
+    fn test_run_length_encoded_tga() {
+        let mut canvas = Canvas::with_alpha(7, 1);
+        for x in 0..4 {
+            canvas.write_pixel(x, 0, Color::new(1.0, 1.0, 1.0));
+        }
+        canvas.write_pixel(4, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.write_pixel_alpha(5, 0, Color::new(0.0, 0.0, 1.0), 0.2);
+
+        let tga = canvas.to_tga(TgaEncoding::RunLength);
+        assert_eq!(tga[2], 10);
+        assert_eq!(tga[16..18], [32, 0x28]);
+        let expected = [
+            0x83, 255, 255, 255, 255, // four opaque white pixels
+            2, 0, 0, 255, 255, 255, 0, 0, 51, 0, 0, 0, 0, // red, blue and transparent black
+        ];
+        assert_eq!(tga[18..tga.len() - FOOTER.len()], expected);
+    }
+
+    #[test]
+    fn test_run_length_packets() {
+        let pixels: Vec<Vec<u8>> = [1, 2, 2, 3, 4, 5, 5, 5]
+            .iter()
+            .map(|value| vec![*value])
+            .collect();
+        let mut bytes = Vec::new();
+        write_run_length_encoded(&mut bytes, &pixels).unwrap();
+        assert_eq!(bytes, [0, 1, 0x81, 2, 1, 3, 4, 0x82, 5]);
+
+        let pixels = vec![vec![9]; 300];
+        let mut bytes = Vec::new();
+        write_run_length_encoded(&mut bytes, &pixels).unwrap();
+        assert_eq!(bytes, [0xff, 9, 0xff, 9, 0xab, 9]);
+    }
+
+    #[test]
+    fn test_oversized_tga_is_rejected() {
+        let canvas = Canvas::new(70_000, 0);
+        assert!(canvas.write_tga(&mut Vec::new(), TgaEncoding::Raw).is_err());
+    }
+}
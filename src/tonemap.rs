@@ -0,0 +1,316 @@
+//! Turns the linear, unbounded colors a render produces into something a file can hold:
+//! tone mapping squeezes them into 0..1, then they're gamma encoded and optionally dithered
+use crate::prelude::*;
+
+/// Operators for mapping linear colors of any brightness into 0..1. Each works on the
+/// channels separately.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ToneMap {
+    /// Cuts off anything above 1, so bright highlights lose their detail
+    Clamp,
+    /// `c / (1 + c)`, which never quite reaches white
+    Reinhard,
+    /// Reinhard scaled so that `white_point` and anything above it maps to 1
+    ExtendedReinhard { white_point: f32 },
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve, with a gentle toe and shoulder
+    Aces,
+    /// `1 - e^(-c * exposure)`, like film that saturates as it takes in more light
+    Exposure { exposure: f32 },
+}
+
+impl ToneMap {
+    fn map_channel(self, channel: f32) -> f32 {
+        let channel = channel.max(0.0);
+        match self {
+            ToneMap::Clamp => channel.min(1.0),
+            ToneMap::Reinhard => channel / (1.0 + channel),
+            ToneMap::ExtendedReinhard { white_point } => {
+                let mapped = channel * (1.0 + channel / (white_point * white_point));
+                (mapped / (1.0 + channel)).min(1.0)
+            }
+            ToneMap::Aces => {
+                let numerator = channel * (2.51 * channel + 0.03);
+                let denominator = channel * (2.43 * channel + 0.59) + 0.14;
+                (numerator / denominator).clamp(0.0, 1.0)
+            }
+            ToneMap::Exposure { exposure } => 1.0 - (-channel * exposure).exp(),
+        }
+    }
+
+    pub fn apply(self, color: Color) -> Color {
+        Color::new(
+            self.map_channel(color.red),
+            self.map_channel(color.green),
+            self.map_channel(color.blue),
+        )
+    }
+}
+
+/// Noise added before colors are quantized, trading banding in smooth gradients for
+/// fine grain
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Dither {
+    None,
+    /// An 8x8 Bayer matrix, which gives a regular cross hatched pattern
+    Ordered,
+    /// Jorge Jimenez's interleaved gradient noise. It isn't true blue noise but shares its
+    /// lack of low frequencies, so the grain looks even without needing a noise texture.
+    BlueNoise,
+}
+
+impl Dither {
+    /// # Returns
+    /// The dither threshold at x, y in 0..1
+    fn threshold(self, x: usize, y: usize) -> f32 {
+        match self {
+            Dither::None => 0.5,
+            Dither::Ordered => {
+                let (x, y) = (x % 8, y % 8);
+                // Interleaving the bits of x ^ y and y in reverse order, so the lowest bits
+                // of the position decide the highest bits of the index, gives the recursive
+                // Bayer pattern
+                let mut index = 0;
+                for bit in 0..3 {
+                    index = (index << 2) | ((x ^ y) >> bit & 1) << 1 | (y >> bit & 1);
+                }
+                (index as f32 + 0.5) / 64.0
+            }
+            Dither::BlueNoise => {
+                let gradient = (0.067_110_56 * x as f64 + 0.005_837_15 * y as f64).fract();
+                (52.982_918_9 * gradient).fract() as f32
+            }
+        }
+    }
+}
+
+/// Encodes a linear channel with the sRGB transfer function, which spends more of the
+/// available precision on dark values where the eye is most sensitive
+pub fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Decodes an sRGB channel, like the ones in most PNG textures, back to linear
+pub fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.040_45 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// The steps run on a canvas before it's written out
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PostProcess {
+    pub tone_map: ToneMap,
+    /// Whether to gamma encode with the sRGB curve, which every format except HDR and PFM
+    /// is assumed to use
+    pub srgb: bool,
+    /// Dithering is sized for 8 bit output, one step being 1/255
+    pub dither: Dither,
+}
+
+impl PostProcess {
+    pub fn new(tone_map: ToneMap) -> Self {
+        Self {
+            tone_map,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_dither(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    pub fn apply(&self, color: Color, x: usize, y: usize) -> Color {
+        let encode = |channel| {
+            if self.srgb {
+                linear_to_srgb(channel)
+            } else {
+                channel
+            }
+        };
+        let offset = match self.dither {
+            Dither::None => 0.0,
+            dither => (dither.threshold(x, y) - 0.5) / 255.0,
+        };
+        let mapped = self.tone_map.apply(color);
+        let [red, green, blue] = [mapped.red, mapped.green, mapped.blue]
+            .map(|channel| (encode(channel) + offset).clamp(0.0, 1.0));
+        Color::new(red, green, blue)
+    }
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        Self {
+            tone_map: ToneMap::Clamp,
+            srgb: true,
+            dither: Dither::None,
+        }
+    }
+}
+
+impl Canvas {
+    /// # Returns
+    /// A copy of the canvas with every pixel run through `post_process`, ready to be written
+    /// to an 8 or 16 bit format. Alpha is kept as it is.
+    pub fn post_processed(&self, post_process: &PostProcess) -> Canvas {
+        let pixels = self
+            .pixels
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, pixel)| post_process.apply(*pixel, x, y))
+                    .collect()
+            })
+            .collect();
+        Canvas {
+            width: self.width,
+            height: self.height,
+            pixels,
+            alpha: self.alpha.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tonemap_tests {
+    use crate::compare_float;
+    use crate::tonemap::*;
+
+    #[test]
+    fn test_tone_map_operators() {
+        let bright = Color::new(0.0, 1.0, 3.0);
+
+        let clamped = ToneMap::Clamp.apply(bright);
+        assert!(clamped.is_same(&Color::new(0.0, 1.0, 1.0)));
+        let reinhard = ToneMap::Reinhard.apply(bright);
+        assert!(reinhard.is_same(&Color::new(0.0, 0.5, 0.75)));
+        let extended = ToneMap::ExtendedReinhard { white_point: 3.0 }.apply(bright);
+        assert!(extended.is_same(&Color::new(0.0, 10.0 / 18.0, 1.0)));
+        let aces = ToneMap::Aces.apply(bright);
+        assert!(aces.is_same(&Color::new(0.0, 2.54 / 3.16, 0.9576)));
+        let exposure = ToneMap::Exposure { exposure: 1.0 }.apply(bright);
+        assert!(exposure.is_same(&Color::new(0.0, 0.632, 0.950)));
+
+        // Negative light isn't physical and maps to black
+        let negative = Color::new(-1.0, -1.0, -1.0);
+        assert!(ToneMap::Aces.apply(negative).is_same(&Color::zero()));
+    }
+
+    #[test]
+    fn test_tone_maps_stay_in_range() {
+        let operators = [
+            ToneMap::Clamp,
+            ToneMap::Reinhard,
+            ToneMap::ExtendedReinhard { white_point: 4.0 },
+            ToneMap::Aces,
+            ToneMap::Exposure { exposure: 2.0 },
+        ];
+        for operator in operators {
+            let mut previous = 0.0;
+            for step in 0..200 {
+                let mapped = operator.map_channel(step as f32 * 0.1);
+                assert!((0.0..=1.0).contains(&mapped));
+                assert!(mapped >= previous, "{:?} isn't monotonic", operator);
+                previous = mapped;
+            }
+        }
+    }
+
+    #[test]
+    fn test_srgb_encoding() {
+        assert_eq!(linear_to_srgb(0.0), 0.0);
+        assert!(compare_float(linear_to_srgb(1.0), 1.0));
+        assert!(compare_float(linear_to_srgb(0.5), 0.7354));
+        assert!(compare_float(linear_to_srgb(0.001), 0.01292));
+
+        for step in 0..=100 {
+            let channel = step as f32 / 100.0;
+            assert!((srgb_to_linear(linear_to_srgb(channel)) - channel).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_bayer_thresholds() {
+        let mut thresholds: Vec<f32> = (0..64)
+            .map(|index| Dither::Ordered.threshold(index % 8, index / 8))
+            .collect();
+        assert_eq!(Dither::Ordered.threshold(0, 0), 0.5 / 64.0);
+        assert_eq!(Dither::Ordered.threshold(1, 0), 32.5 / 64.0);
+        assert_eq!(Dither::Ordered.threshold(1, 1), 16.5 / 64.0);
+        assert_eq!(
+            Dither::Ordered.threshold(8, 8),
+            Dither::Ordered.threshold(0, 0)
+        );
+
+        thresholds.sort_by(f32::total_cmp);
+        for (index, threshold) in thresholds.iter().enumerate() {
+            assert_eq!(*threshold, (index as f32 + 0.5) / 64.0);
+        }
+    }
+
+    #[test]
+    fn test_dithering_keeps_the_average_level() {
+        // A level between two 8 bit values should come out as a mix of both
+        let level = 100.25 / 255.0;
+        let mut canvas = Canvas::new(16, 16);
+        for y in 0..16 {
+            for x in 0..16 {
+                canvas.write_pixel(x, y, Color::new(level, level, level));
+            }
+        }
+
+        for dither in [Dither::Ordered, Dither::BlueNoise] {
+            let post_process = PostProcess {
+                srgb: false,
+                ..PostProcess::default()
+            }
+            .with_dither(dither);
+            let dithered = canvas.post_processed(&post_process);
+
+            let samples: Vec<u16> = dithered
+                .pixels
+                .iter()
+                .flatten()
+                .map(|pixel| pixel.to_scaled(255)[0])
+                .collect();
+            assert!(samples
+                .iter()
+                .all(|sample| *sample == 100 || *sample == 101));
+            let average = samples.iter().map(|sample| f32::from(*sample)).sum::<f32>() / 256.0;
+            assert!(
+                (average - 100.25).abs() < 0.05,
+                "{:?} averaged {}",
+                dither,
+                average
+            );
+        }
+    }
+
+    #[test]
+    fn test_post_processing_a_canvas() {
+        let mut canvas = Canvas::with_alpha(2, 1);
+        canvas.write_pixel_alpha(0, 0, Color::new(0.5, 2.0, -1.0), 0.25);
+
+        let processed = canvas.post_processed(&PostProcess::default());
+        assert!(processed
+            .pixel_at(0, 0)
+            .is_same(&Color::new(0.7354, 1.0, 0.0)));
+        assert_eq!(processed.alpha_at(0, 0), 0.25);
+        assert_eq!(processed.alpha_at(1, 0), 0.0);
+
+        let processed = canvas.post_processed(&PostProcess::new(ToneMap::Reinhard));
+        assert!(compare_float(
+            processed.pixel_at(0, 0).green,
+            linear_to_srgb(2.0 / 3.0)
+        ));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::polynomial::Polynomial;
+use crate::prelude::*;
+
+/// A ring around the y axis, lying flat in the xz plane: a circle of `minor_radius` swept
+/// around a circle of `major_radius`
+pub struct Torus {
+    pub transform: Transform,
+    pub material: Material,
+    /// From the centre of the torus to the middle of its tube
+    pub major_radius: f32,
+    /// The radius of the tube
+    pub minor_radius: f32,
+}
+
+impl Torus {
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+            major_radius: 1.0,
+            minor_radius: 0.25,
+        }
+    }
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Torus {
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+    fn material(&self) -> &Material {
+        &self.material
+    }
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    /// A point p is on the torus where
+    /// `(|p|² - R² - r²)² + 4R²(y² - r²) = 0`,
+    /// and putting in the ray gives a quartic in t. Its roots are only searched for
+    /// between where the ray enters and leaves the bounding box.
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let Some((t_min, t_max)) = self.bounds().intersect(ray) else {
+            return Intersections::new(vec![]);
+        };
+
+        let origin = ray.origin.position;
+        let [ox, oy, oz] = [origin.x, origin.y, origin.z].map(f64::from);
+        let [dx, dy, dz] = [ray.direction.x, ray.direction.y, ray.direction.z].map(f64::from);
+        let major_squared = f64::from(self.major_radius).powi(2);
+        let minor_squared = f64::from(self.minor_radius).powi(2);
+
+        let direction_squared = dx * dx + dy * dy + dz * dz;
+        let origin_dot_direction = ox * dx + oy * dy + oz * dz;
+        // |o|² - R² - r², the constant part of |p|² - R² - r²
+        let offset = ox * ox + oy * oy + oz * oz - major_squared - minor_squared;
+        let quartic = Polynomial::new(&[
+            offset * offset + 4.0 * major_squared * (oy * oy - minor_squared),
+            4.0 * origin_dot_direction * offset + 8.0 * major_squared * oy * dy,
+            2.0 * direction_squared * offset
+                + 4.0 * origin_dot_direction * origin_dot_direction
+                + 4.0 * major_squared * dy * dy,
+            4.0 * direction_squared * origin_dot_direction,
+            direction_squared * direction_squared,
+        ]);
+
+        // Widened a little so hits right on the edge of the box aren't lost to rounding
+        let (t_min, t_max) = (f64::from(t_min - EPSILON), f64::from(t_max + EPSILON));
+        quartic
+            .roots_in(t_min, t_max)
+            .into_iter()
+            .map(|t| Intersection::new(t as f32, self))
+            .collect()
+    }
+
+    /// The gradient of the torus equation, scaled down by 4
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let Vector { x, y, z } = point.position;
+        let major_squared = self.major_radius * self.major_radius;
+        let offset = x * x + y * y + z * z - major_squared - self.minor_radius * self.minor_radius;
+
+        Vector::new(x * offset, y * (offset + 2.0 * major_squared), z * offset)
+    }
+
+    fn bounds(&self) -> Bounds {
+        let outer = self.major_radius + self.minor_radius;
+        Bounds::new(
+            Point::new(-outer, -self.minor_radius, -outer),
+            Point::new(outer, self.minor_radius, outer),
+        )
+    }
+}
+
+#[cfg(test)]
+mod torus_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+    use crate::shape::shape_tests::ray;
+
+    fn assert_ts(xs: &Intersections, expected: &[f32]) {
+        let ts: Vec<f32> = xs.iter().map(|intersection| intersection.t).collect();
+        assert_eq!(ts.len(), expected.len(), "{:?} != {:?}", ts, expected);
+        for (t, expected) in ts.iter().zip(expected) {
+            assert!(compare_float(*t, *expected), "{:?} != {:?}", ts, expected);
+        }
+    }
+
+    #[test]
+    fn test_ray_through_both_sides_of_ring() {
+        let torus = Torus::new();
+        let xs = torus.local_intersect(&ray((-5.0, 0.0, 0.0), (1.0, 0.0, 0.0)));
+
+        assert_ts(&xs, &[3.75, 4.25, 5.75, 6.25]);
+        assert!(std::ptr::addr_eq(xs[0].object, &torus));
+    }
+
+    #[test]
+    fn test_ray_through_tube_from_above() {
+        let torus = Torus::new();
+
+        let xs = torus.local_intersect(&ray((1.0, 5.0, 0.0), (0.0, -1.0, 0.0)));
+        assert_ts(&xs, &[4.75, 5.25]);
+        // From inside the tube one hit is behind the ray
+        let xs = torus.local_intersect(&ray((0.0, 0.0, -1.0), (0.0, 0.0, 1.0)));
+        assert_ts(&xs, &[-0.25, 0.25, 1.75, 2.25]);
+    }
+
+    #[test]
+    fn test_ray_misses_torus() {
+        let torus = Torus::new();
+        let cases = [
+            // Straight down the hole in the middle
+            ((0.0, 5.0, 0.0), (0.0, -1.0, 0.0)),
+            ((0.0, 1.0, -5.0), (0.0, 0.0, 1.0)),
+            ((-5.0, 0.0, 2.0), (1.0, 0.0, 0.0)),
+        ];
+
+        for (origin, direction) in cases {
+            let xs = torus.local_intersect(&ray(origin, direction));
+            assert!(xs.is_empty(), "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_ray_grazing_tube() {
+        let torus = Torus::new();
+        let xs = torus.local_intersect(&ray((-5.0, 0.25, 0.0), (1.0, 0.0, 0.0)));
+
+        // Just touching the top of the tube is a repeated root, so each side hits once
+        assert_ts(&xs, &[4.0, 6.0]);
+    }
+
+    #[test]
+    fn test_normal_on_torus() {
+        let torus = Torus::new();
+        let cases = [
+            ((1.25, 0.0, 0.0), (1.0, 0.0, 0.0)),
+            ((0.75, 0.0, 0.0), (-1.0, 0.0, 0.0)),
+            ((1.0, 0.25, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, -0.25, -1.0), (0.0, -1.0, 0.0)),
+            ((0.0, 0.0, -1.25), (0.0, 0.0, -1.0)),
+        ];
+
+        for (point, normal) in cases {
+            let mut found = torus.local_normal_at(Point::new(point.0, point.1, point.2));
+            found.normalize();
+            assert!(found.is_same(&Vector::new(normal.0, normal.1, normal.2)));
+        }
+    }
+
+    #[test]
+    fn test_transformed_torus() {
+        let mut torus = Torus::new();
+        torus.transform = Transform::new(
+            Matrix4::from_identity()
+                .rotate_x(std::f32::consts::PI / 2.0)
+                .translate(0.0, 0.0, 5.0),
+        )
+        .unwrap();
+
+        // Standing on its edge, facing the ray, so the ray goes through the hole
+        let xs = torus.intersect(&ray((0.0, 0.0, 0.0), (0.0, 0.0, 1.0)));
+        assert!(xs.is_empty());
+        let xs = torus.intersect(&ray((1.0, 0.0, 0.0), (0.0, 0.0, 1.0)));
+        assert_ts(&xs, &[4.75, 5.25]);
+        assert!(torus
+            .normal_at(Point::new(1.0, 0.0, 4.75))
+            .is_same(&Vector::new(0.0, 0.0, -1.0)));
+    }
+}
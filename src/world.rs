@@ -0,0 +1,198 @@
+use crate::prelude::*;
+
+/// Everything in a scene: the objects rays can hit and the lights that shade them
+pub struct World {
+    pub objects: Vec<Box<dyn Shape>>,
+    pub lights: Vec<PointLight>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            lights: Vec::new(),
+        }
+    }
+
+    /// # Returns
+    /// Every intersection of the ray with every object in the world, sorted by t
+    pub fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        self.objects
+            .iter()
+            .flat_map(|object| object.intersect(ray))
+            .collect()
+    }
+
+    /// # Returns
+    /// The color at a precomputed intersection, lit by every light in the world that can see it
+    pub fn shade_hit(&self, comps: &Computations) -> Color {
+        self.lights.iter().fold(Color::BLACK, |color, light| {
+            color
+                + lighting(
+                    comps.object.material(),
+                    light,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    self.is_shadowed(comps.over_point, light),
+                )
+        })
+    }
+
+    /// Casts a ray from `point` towards `light`
+    /// # Returns
+    /// Whether any object sits between the two
+    pub fn is_shadowed(&self, point: Point, light: &PointLight) -> bool {
+        let mut direction = light.position - point;
+        let distance = direction.magnitude();
+        direction.normalize();
+
+        let shadow_ray = Ray::new(point, direction);
+        self.intersect(&shadow_ray)
+            .hit()
+            .is_some_and(|hit| hit.t < distance)
+    }
+
+    /// # Returns
+    /// The color seen along the ray, or black if it doesn't hit anything
+    pub fn color_at(&self, ray: &Ray) -> Color {
+        match self.intersect(ray).hit() {
+            Some(hit) => self.shade_hit(&hit.prepare_computations(ray)),
+            None => Color::BLACK,
+        }
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod world_tests {
+    use crate::compare_float;
+    use crate::prelude::*;
+
+    /// A light at the top left front, shining on two spheres sharing the origin
+    pub(crate) fn default_world() -> World {
+        let mut outer = Sphere::new();
+        outer.material.color = Color::new(0.8, 1.0, 0.6);
+        outer.material.diffuse = 0.7;
+        outer.material.specular = 0.2;
+        let mut inner = Sphere::new();
+        inner.transform = Transform::new(Matrix4::scaling(0.5, 0.5, 0.5)).unwrap();
+
+        World {
+            objects: vec![Box::new(outer), Box::new(inner)],
+            lights: vec![PointLight::new(
+                Point::new(-10.0, 10.0, -10.0),
+                Color::WHITE,
+            )],
+        }
+    }
+
+    #[test]
+    fn test_creating_world() {
+        let world = World::new();
+
+        assert!(world.objects.is_empty());
+        assert!(world.lights.is_empty());
+    }
+
+    #[test]
+    fn test_intersecting_world() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = world.intersect(&ray);
+
+        let ts: Vec<f32> = xs.iter().map(|intersection| intersection.t).collect();
+        assert_eq!(ts.len(), 4);
+        for (t, expected) in ts.iter().zip([4.0, 4.5, 5.5, 6.0]) {
+            assert!(compare_float(*t, expected));
+        }
+    }
+
+    #[test]
+    fn test_shading_intersection() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let comps = Intersection::new(4.0, world.objects[0].as_ref()).prepare_computations(&ray);
+
+        let color = world.shade_hit(&comps);
+        assert!(color.is_same(&Color::new(0.38066, 0.47583, 0.2855)));
+    }
+
+    #[test]
+    fn test_shading_intersection_from_inside() {
+        let mut world = default_world();
+        world.lights = vec![PointLight::new(Point::new(0.0, 0.25, 0.0), Color::WHITE)];
+        let ray = Ray::new(Point::zero(), Vector::new(0.0, 0.0, 1.0));
+        let comps = Intersection::new(0.5, world.objects[1].as_ref()).prepare_computations(&ray);
+
+        let color = world.shade_hit(&comps);
+        assert!(color.is_same(&Color::new(0.90498, 0.90498, 0.90498)));
+    }
+
+    #[test]
+    fn test_color_when_ray_misses() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+        assert_eq!(world.color_at(&ray), Color::BLACK);
+    }
+
+    #[test]
+    fn test_color_when_ray_hits() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(world
+            .color_at(&ray)
+            .is_same(&Color::new(0.38066, 0.47583, 0.2855)));
+    }
+
+    #[test]
+    fn test_color_with_intersection_behind_ray() {
+        let mut world = default_world();
+        world.objects[0].material_mut().ambient = 1.0;
+        world.objects[1].material_mut().ambient = 1.0;
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.75), Vector::new(0.0, 0.0, -1.0));
+
+        // The ray starts between the spheres, so it sees the inner one
+        let color = world.color_at(&ray);
+        assert_eq!(color, world.objects[1].material().color);
+    }
+
+    #[test]
+    fn test_shadows() {
+        let world = default_world();
+        let light = &world.lights[0];
+
+        // Nothing is collinear with the point and the light
+        assert!(!world.is_shadowed(Point::new(0.0, 10.0, 0.0), light));
+        // The spheres are between the point and the light
+        assert!(world.is_shadowed(Point::new(10.0, -10.0, 10.0), light));
+        // The light is between the spheres and the point
+        assert!(!world.is_shadowed(Point::new(-20.0, 20.0, -20.0), light));
+        // The point is between the spheres and the light
+        assert!(!world.is_shadowed(Point::new(-2.0, 2.0, -2.0), light));
+    }
+
+    #[test]
+    fn test_shading_intersection_in_shadow() {
+        let mut second = Sphere::new();
+        second.transform = Transform::new(Matrix4::translation(0.0, 0.0, 10.0)).unwrap();
+        let mut world = World::new();
+        world
+            .lights
+            .push(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE));
+        world.objects.push(Box::new(Sphere::new()));
+        world.objects.push(Box::new(second));
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let comps = Intersection::new(4.0, world.objects[1].as_ref()).prepare_computations(&ray);
+
+        let color = world.shade_hit(&comps);
+        assert!(color.is_same(&Color::new(0.1, 0.1, 0.1)));
+    }
+}
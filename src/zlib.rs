@@ -0,0 +1,663 @@
+//! Just enough of zlib (RFC 1950) and DEFLATE (RFC 1951) to read and write PNG image data.
+use std::fmt;
+
+/// Base length and number of extra bits for each length code, starting at code 257
+pub(crate) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+pub(crate) const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distance and number of extra bits for each distance code
+pub(crate) const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+pub(crate) const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// How many earlier positions with the same hash are checked before settling for the best so far
+const MAX_CHAIN_LENGTH: usize = 128;
+const HASH_BITS: u32 = 15;
+const MAX_STORED_BLOCK: usize = 65535;
+
+/// The order code length code lengths are stored in for dynamic Huffman blocks
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ZlibError {
+    /// Not a deflate stream, or the header check bits are wrong
+    InvalidHeader,
+    /// The stream needs a preset dictionary, which PNG never uses
+    PresetDictionary,
+    InvalidBlockType,
+    /// A stored block's length doesn't match its one's complement
+    InvalidStoredLength,
+    /// The code lengths of a dynamic block don't describe a valid Huffman code
+    InvalidCodeLengths,
+    /// A bit sequence that isn't one of the block's codes, or a reserved symbol
+    InvalidSymbol,
+    /// A back reference to before the start of the data
+    InvalidDistance,
+    ChecksumMismatch,
+    UnexpectedEnd,
+}
+
+impl fmt::Display for ZlibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ZlibError::InvalidHeader => "Invalid zlib header",
+            ZlibError::PresetDictionary => "zlib preset dictionaries are not supported",
+            ZlibError::InvalidBlockType => "Invalid deflate block type",
+            ZlibError::InvalidStoredLength => "Stored block length is corrupt",
+            ZlibError::InvalidCodeLengths => "Invalid Huffman code lengths",
+            ZlibError::InvalidSymbol => "Invalid Huffman symbol",
+            ZlibError::InvalidDistance => "Back reference is too far back",
+            ZlibError::ChecksumMismatch => "Adler-32 checksum doesn't match",
+            ZlibError::UnexpectedEnd => "Compressed data ended early",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ZlibError {}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let mut a = 1;
+    let mut b = 0;
+
+    // 5552 bytes is the most that can be summed before b could overflow a u32
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    (b << 16) | a
+}
+
+/// # Returns
+/// `data` as a zlib stream: a two byte header, the deflated data and an Adler-32 checksum
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    // 32K window, default compression level. The header has to be a multiple of 31.
+    let mut stream = vec![0x78, 0x9c];
+    stream.extend(deflate(data));
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+/// Reverses `compress`, checking the header and the Adler-32 checksum of the output
+pub fn decompress(stream: &[u8]) -> Result<Vec<u8>, ZlibError> {
+    let (compression_info, flags) = match stream {
+        [compression_info, flags, ..] => (*compression_info, *flags),
+        _ => return Err(ZlibError::UnexpectedEnd),
+    };
+    let is_deflate = compression_info & 0x0f == 8 && compression_info >> 4 <= 7;
+    if !is_deflate || (u16::from(compression_info) << 8 | u16::from(flags)) % 31 != 0 {
+        return Err(ZlibError::InvalidHeader);
+    }
+    if flags & 0x20 != 0 {
+        return Err(ZlibError::PresetDictionary);
+    }
+
+    let (data, consumed) = inflate(&stream[2..])?;
+    let checksum = stream
+        .get(2 + consumed..2 + consumed + 4)
+        .ok_or(ZlibError::UnexpectedEnd)?;
+    if checksum != adler32(&data).to_be_bytes() {
+        return Err(ZlibError::ChecksumMismatch);
+    }
+    Ok(data)
+}
+
+/// # Returns
+/// The decompressed data and how many bytes of `data` the deflate stream took up
+fn inflate(data: &[u8]) -> Result<(Vec<u8>, usize), ZlibError> {
+    let mut reader = BitReader::new(data);
+    let mut output = Vec::new();
+
+    loop {
+        let is_final = reader.read_bits(1)? == 1;
+        match reader.read_bits(2)? {
+            0 => inflate_stored(&mut reader, &mut output)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(ZlibError::InvalidBlockType),
+        }
+        if is_final {
+            break;
+        }
+    }
+    Ok((output, reader.bytes_consumed()))
+}
+
+fn inflate_stored(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<(), ZlibError> {
+    reader.align_to_byte();
+    let length = reader.read_bits(16)?;
+    let complement = reader.read_bits(16)?;
+    if length != !complement & 0xffff {
+        return Err(ZlibError::InvalidStoredLength);
+    }
+    output.extend_from_slice(reader.read_bytes(length as usize)?);
+    Ok(())
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ZlibError> {
+    loop {
+        let symbol = usize::from(literals.decode(reader)?);
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let extra = reader.read_bits(LENGTH_EXTRA_BITS[index])?;
+                let length = usize::from(LENGTH_BASE[index]) + extra as usize;
+
+                let index = usize::from(distances.decode(reader)?);
+                if index >= DISTANCE_BASE.len() {
+                    return Err(ZlibError::InvalidSymbol);
+                }
+                let extra = reader.read_bits(DISTANCE_EXTRA_BITS[index])?;
+                let distance = usize::from(DISTANCE_BASE[index]) + extra as usize;
+                if distance > output.len() {
+                    return Err(ZlibError::InvalidDistance);
+                }
+
+                // The copy can overlap what it's writing, e.g. a distance of 1 repeats one byte
+                let start = output.len() - distance;
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+            _ => return Err(ZlibError::InvalidSymbol),
+        }
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+
+    let literals = Huffman::new(&lengths).expect("Fixed codes are valid");
+    let distances = Huffman::new(&[5; 30]).expect("Fixed codes are valid");
+    (literals, distances)
+}
+
+/// Reads the Huffman codes at the start of a dynamic block. The code lengths themselves are
+/// Huffman coded and run length encoded, with symbols 16 to 18 standing for repeats.
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), ZlibError> {
+    let literal_count = reader.read_bits(5)? as usize + 257;
+    let distance_count = reader.read_bits(5)? as usize + 1;
+    let code_length_count = reader.read_bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(ZlibError::InvalidCodeLengths);
+    }
+
+    let mut code_length_lengths = [0; 19];
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_length_lengths[*index] = reader.read_bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_lengths.decode(reader)?;
+        let (length, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or(ZlibError::InvalidCodeLengths)?;
+                (previous, 3 + reader.read_bits(2)?)
+            }
+            17 => (0, 3 + reader.read_bits(3)?),
+            _ => (0, 11 + reader.read_bits(7)?),
+        };
+        if lengths.len() + repeat as usize > literal_count + distance_count {
+            return Err(ZlibError::InvalidCodeLengths);
+        }
+        lengths.resize(lengths.len() + repeat as usize, length);
+    }
+    if lengths[256] == 0 {
+        // Without an end of block code the block could never finish
+        return Err(ZlibError::InvalidCodeLengths);
+    }
+
+    let literals = Huffman::new(&lengths[..literal_count])?;
+    let distances = Huffman::new(&lengths[literal_count..])?;
+    Ok((literals, distances))
+}
+
+/// A canonical Huffman code, stored as the number of codes of each length and the symbols
+/// in code order. Decoding walks down one length at a time, which is slow but tiny.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, ZlibError> {
+        let mut counts = [0_u16; 16];
+        for length in lengths {
+            counts[usize::from(*length)] += 1;
+        }
+        counts[0] = 0;
+
+        // More codes of a length than there is room for means the lengths are corrupt
+        let mut available: i32 = 1;
+        for count in &counts[1..] {
+            available = available * 2 - i32::from(*count);
+            if available < 0 {
+                return Err(ZlibError::InvalidCodeLengths);
+            }
+        }
+
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|symbol| lengths[usize::from(*symbol)] != 0)
+            .collect();
+        symbols.sort_by_key(|symbol| lengths[usize::from(*symbol)]);
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ZlibError> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+
+        for count in &self.counts[1..] {
+            code |= reader.read_bits(1)? as i32;
+            let count = i32::from(*count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ZlibError::InvalidSymbol)
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            bit: 0,
+        }
+    }
+
+    fn read_bits(&mut self, count: u8) -> Result<u32, ZlibError> {
+        let mut value = 0;
+        for index in 0..count {
+            let byte = self
+                .data
+                .get(self.position)
+                .ok_or(ZlibError::UnexpectedEnd)?;
+            value |= u32::from((byte >> self.bit) & 1) << index;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], ZlibError> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + count)
+            .ok_or(ZlibError::UnexpectedEnd)?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn bytes_consumed(&self) -> usize {
+        self.position + usize::from(self.bit > 0)
+    }
+}
+
+/// Compresses with LZ77 and the fixed Huffman codes, falling back to stored blocks if that
+/// turns out bigger (e.g. for noisy data where most literals need 9 bit codes).
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let compressed = deflate_fixed(data);
+    let stored_length = data.len() + 5 * (data.len() / MAX_STORED_BLOCK + 1);
+
+    if compressed.len() > stored_length {
+        deflate_stored(data)
+    } else {
+        compressed
+    }
+}
+
+fn deflate_stored(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() + 5 * (data.len() / MAX_STORED_BLOCK + 1));
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+
+    if blocks.peek().is_none() {
+        // Even an empty stream needs one final block
+        output.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let length = block.len() as u16;
+        output.push(u8::from(is_final));
+        output.extend(length.to_le_bytes());
+        output.extend((!length).to_le_bytes());
+        output.extend(block);
+    }
+    output
+}
+
+fn deflate_fixed(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    // Final block, fixed Huffman codes
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+    let mut position = 0;
+
+    while position < data.len() {
+        let (length, distance) = longest_match(data, position, &head, &previous);
+
+        if length >= MIN_MATCH {
+            write_length(&mut writer, length);
+            write_distance(&mut writer, distance);
+        } else {
+            write_literal(&mut writer, u16::from(data[position]));
+        }
+
+        let step = length.max(1);
+        for inserted in position..position + step {
+            if inserted + MIN_MATCH <= data.len() {
+                let hash = hash(&data[inserted..]);
+                previous[inserted % WINDOW_SIZE] = head[hash];
+                head[hash] = inserted;
+            }
+        }
+        position += step;
+    }
+
+    write_literal(&mut writer, 256);
+    writer.finish()
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+    (value.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+/// # Returns
+/// The length and distance of the longest earlier run of bytes matching those at `position`
+fn longest_match(
+    data: &[u8],
+    position: usize,
+    head: &[usize],
+    previous: &[usize],
+) -> (usize, usize) {
+    if position + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max_length = MAX_MATCH.min(data.len() - position);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(&data[position..])];
+    let mut chain = 0;
+
+    while candidate != usize::MAX && chain < MAX_CHAIN_LENGTH {
+        let distance = position - candidate;
+        if distance > WINDOW_SIZE {
+            break;
+        }
+
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[position..position + max_length])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best.0 {
+            best = (length, distance);
+            if length == max_length {
+                break;
+            }
+        }
+
+        let next = previous[candidate % WINDOW_SIZE];
+        // Older entries in the ring buffer get overwritten, so stop once the chain stops going back
+        if next == usize::MAX || next >= candidate {
+            break;
+        }
+        candidate = next;
+        chain += 1;
+    }
+    best
+}
+
+fn write_literal(writer: &mut BitWriter, symbol: u16) {
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+    writer.write_huffman_code(code, length);
+}
+
+fn write_length(writer: &mut BitWriter, length: usize) {
+    let index = LENGTH_BASE
+        .iter()
+        .rposition(|base| usize::from(*base) <= length)
+        .expect("Matches are at least 3 long");
+    write_literal(writer, 257 + index as u16);
+    let extra = length - usize::from(LENGTH_BASE[index]);
+    writer.write_bits(extra as u32, LENGTH_EXTRA_BITS[index]);
+}
+
+fn write_distance(writer: &mut BitWriter, distance: usize) {
+    let index = DISTANCE_BASE
+        .iter()
+        .rposition(|base| usize::from(*base) <= distance)
+        .expect("Distances are at least 1");
+    writer.write_huffman_code(index as u16, 5);
+    let extra = distance - usize::from(DISTANCE_BASE[index]);
+    writer.write_bits(extra as u32, DISTANCE_EXTRA_BITS[index]);
+}
+
+/// Packs bits starting from the least significant bit of each byte, as DEFLATE expects
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bit_count: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            buffer: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn write_bits(&mut self, value: u32, count: u8) {
+        for bit in 0..count {
+            self.buffer |= ((value >> bit) & 1) << self.bit_count;
+            self.bit_count += 1;
+            if self.bit_count == 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer = 0;
+                self.bit_count = 0;
+            }
+        }
+    }
+
+    /// Huffman codes are the one thing packed most significant bit first
+    fn write_huffman_code(&mut self, code: u16, length: u8) {
+        let reversed = code.reverse_bits() >> (16 - length);
+        self.write_bits(u32::from(reversed), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod zlib_tests {
+    use crate::zlib::*;
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+        // Long enough that the running sums have to be reduced part way through
+        let data = vec![0xff; 100_000];
+        let (mut a, mut b) = (1_u64, 0_u64);
+        for byte in &data {
+            a = (a + u64::from(*byte)) % 65521;
+            b = (b + a) % 65521;
+        }
+        assert_eq!(adler32(&data), ((b << 16) | a) as u32);
+    }
+
+    #[test]
+    fn test_zlib_header_and_checksum() {
+        let data = b"hello hello hello hello";
+        let stream = compress(data);
+
+        assert_eq!((u16::from(stream[0]) << 8 | u16::from(stream[1])) % 31, 0);
+        assert_eq!(stream[0] & 0x0f, 8, "Compression method must be deflate");
+        assert_eq!(stream[stream.len() - 4..], adler32(data).to_be_bytes());
+    }
+
+    #[test]
+    fn test_repeated_data_compresses() {
+        let data = vec![7; 10_000];
+        assert!(compress(&data).len() < 100);
+    }
+
+    #[test]
+    fn test_stored_blocks() {
+        assert_eq!(deflate_stored(b""), vec![1, 0, 0, 0xff, 0xff]);
+        assert_eq!(deflate_stored(b"ab"), vec![1, 2, 0, 0xfd, 0xff, b'a', b'b']);
+
+        let data = vec![1; MAX_STORED_BLOCK + 1];
+        let stored = deflate_stored(&data);
+        assert_eq!(stored[0], 0, "First of two blocks isn't final");
+        assert_eq!(stored[5 + MAX_STORED_BLOCK], 1, "Second block is final");
+        assert_eq!(stored.len(), data.len() + 10);
+    }
+
+    #[test]
+    fn test_fixed_huffman_encoding() {
+        // A single literal 'a' followed by the end of block code, checked against zlib's output
+        assert_eq!(deflate_fixed(b"a"), vec![0x4b, 0x04, 0x00]);
+        assert_eq!(deflate_fixed(b""), vec![0x03, 0x00]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = b"It was the best of times, it was the worst of times".repeat(20);
+        let noise: Vec<u8> = (0..70_000_u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+
+        for data in [&b""[..], b"a", &text, &noise, &vec![0; 100_000]] {
+            assert_eq!(decompress(&compress(data)).expect("Valid stream"), data);
+        }
+    }
+
+    #[test]
+    fn test_dynamic_huffman_block() {
+        // Produced by zlib at level 9, which picks a dynamic Huffman block for this input
+        let stream = [
+            0x78, 0xda, 0xb5, 0xcb, 0xd1, 0x01, 0x80, 0x10, 0x14, 0x46, 0xe1, 0x55, 0xfe, 0x16,
+            0x68, 0x96, 0x1e, 0x2c, 0x40, 0x11, 0x15, 0x37, 0x84, 0x98, 0xbe, 0xbb, 0x44, 0xcf,
+            0xe7, 0x3b, 0xc2, 0x6a, 0xc4, 0xe2, 0xd6, 0x13, 0x2a, 0x51, 0x0b, 0x30, 0xf4, 0xe2,
+            0x28, 0xfe, 0xce, 0xa0, 0xaa, 0x13, 0x1e, 0xce, 0x97, 0x1c, 0x1d, 0x1b, 0xed, 0x33,
+            0xc4, 0x6f, 0x78, 0x91, 0xec, 0x7c, 0x87, 0x62, 0xd4, 0xdc, 0x63, 0x61, 0x5c, 0xd5,
+            0x9c, 0x86, 0x0e, 0xb8, 0x5c, 0x2c, 0x94, 0xf8, 0xdd, 0xf3, 0xf4, 0x01, 0xb2, 0xee,
+            0x3f, 0x00,
+        ];
+        let mut expected = b"The quick brown fox jumps over the lazy dog. ".repeat(3);
+        expected.extend_from_slice(b"Pack my box with five dozen liquor jugs!");
+
+        assert_eq!(decompress(&stream).expect("Valid stream"), expected);
+    }
+
+    #[test]
+    fn test_corrupt_streams() {
+        let stream = compress(b"hello hello hello");
+
+        assert_eq!(decompress(&[0x78]), Err(ZlibError::UnexpectedEnd));
+        assert_eq!(
+            decompress(&[0x78, 0x9d, 0x03, 0x00]),
+            Err(ZlibError::InvalidHeader)
+        );
+        assert_eq!(
+            decompress(&[0x78, 0xbb, 0, 0, 0, 0]),
+            Err(ZlibError::PresetDictionary)
+        );
+        assert_eq!(
+            decompress(&stream[..stream.len() - 1]),
+            Err(ZlibError::UnexpectedEnd)
+        );
+
+        let mut bad_checksum = stream.clone();
+        *bad_checksum.last_mut().expect("Stream isn't empty") ^= 1;
+        assert_eq!(decompress(&bad_checksum), Err(ZlibError::ChecksumMismatch));
+
+        // Reserved block type 3
+        assert_eq!(
+            decompress(&[0x78, 0x9c, 0x07]),
+            Err(ZlibError::InvalidBlockType)
+        );
+        // Stored block whose length and complement disagree
+        assert_eq!(
+            decompress(&[0x78, 0x9c, 0x01, 0x01, 0x00, 0x00, 0x00]),
+            Err(ZlibError::InvalidStoredLength)
+        );
+    }
+}